@@ -1,10 +1,20 @@
 use std::path::PathBuf;
 
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter};
 
-use crate::git::{clone, dag, diff, init, log, refs, repository};
+use crate::git::{
+    archive, blame, branches, clone, combined, dag, diff, grep, history, init, log, refs,
+    repository, search, stats, tree, verify,
+};
 use crate::progress::emit_error;
-use crate::types::{AddRepoResult, CloneResult, CommitDag, CommitInfo, CommitLogPage, RefInfo, RepositoryInfo, SourceDetection, UsbDevice};
+use crate::types::{
+    AddRepoResult, ArchiveOptions, ArchiveResult, BlameOptions, BlobContent, BlobOptions,
+    BranchStatus, BranchStatusOptions, ChangedFile, CloneResult, CombinedFileDiff, CommitDag,
+    CommitInfo, CommitLogPage, CommitSearchOptions, CommitSearchSummary, CommitStats, DiffOptions,
+    FileBlame, FileDiff, FileHistoryEntry, GrepOptions, GrepResult, LogFilter, LogSort, ParentDiff,
+    PickaxeHit, PickaxeOptions, RefInfo, RepositoryInfo, SignatureVerification, SourceDetection,
+    TreeListing, TreeOptions, UsbDevice, VerifyOptions,
+};
 use crate::usb::detect;
 
 #[tauri::command]
@@ -27,15 +37,44 @@ pub async fn discover_repositories(root: String, max_depth: Option<usize>) -> Re
     Ok(repos)
 }
 
+/// Each hit is emitted as a `commit-search-hit` event while the search runs;
+/// the returned summary marks the end of the search.
+#[tauri::command]
+pub async fn search_commits(
+    app_handle: AppHandle,
+    mount_point: String,
+    query: String,
+    options: Option<CommitSearchOptions>,
+) -> Result<CommitSearchSummary, String> {
+    let root = PathBuf::from(&mount_point);
+    let options = options.unwrap_or_default();
+    search::search_commits(&root, &query, &options, |hit| {
+        let _ = app_handle.emit("commit-search-hit", hit);
+    })
+    .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_commit_log(
     repo_path: String,
     start_oid: Option<String>,
+    cursor: Option<String>,
     limit: Option<usize>,
+    filter: Option<LogFilter>,
+    sort: Option<LogSort>,
 ) -> Result<CommitLogPage, String> {
     let path = PathBuf::from(&repo_path);
     let limit = limit.unwrap_or(50);
-    log::get_commit_log(&path, start_oid.as_deref(), limit).map_err(|e| e.to_string())
+    let filter = filter.unwrap_or_default();
+    log::get_commit_log(
+        &path,
+        start_oid.as_deref(),
+        cursor.as_deref(),
+        limit,
+        &filter,
+        sort.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -44,12 +83,137 @@ pub async fn get_commit_detail(repo_path: String, oid: String) -> Result<CommitI
     log::get_commit_detail(&path, &oid).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_file_history(
+    repo_path: String,
+    file_path: String,
+    start_oid: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<FileHistoryEntry>, String> {
+    let path = PathBuf::from(&repo_path);
+    let limit = limit.unwrap_or(100);
+    history::get_file_history(&path, &file_path, start_oid.as_deref(), limit)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_blame(
+    repo_path: String,
+    file_path: String,
+    rev: Option<String>,
+    options: Option<BlameOptions>,
+) -> Result<FileBlame, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    blame::blame_file(&path, &file_path, rev.as_deref(), &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tree(
+    repo_path: String,
+    rev: Option<String>,
+    path: Option<String>,
+    options: Option<TreeOptions>,
+) -> Result<TreeListing, String> {
+    let repo = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    tree::list_tree(&repo, rev.as_deref(), path.as_deref().unwrap_or_default(), &options)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_file_content(
+    repo_path: String,
+    rev: Option<String>,
+    path: String,
+    options: Option<BlobOptions>,
+) -> Result<BlobContent, String> {
+    let repo = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    tree::read_file(&repo, rev.as_deref(), &path, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn export_archive(
+    app_handle: AppHandle,
+    repo_path: String,
+    rev: Option<String>,
+    output_path: String,
+    options: Option<ArchiveOptions>,
+) -> Result<ArchiveResult, String> {
+    let repo = PathBuf::from(&repo_path);
+    let output = PathBuf::from(&output_path);
+    let options = options.unwrap_or_default();
+    archive::export_archive(&repo, rev.as_deref(), &output, &options, Some(&app_handle)).map_err(
+        |e| {
+            emit_error(Some(&app_handle), "archive-progress", "archive.failed");
+            e.to_string()
+        },
+    )
+}
+
+#[tauri::command]
+pub async fn grep_tree(
+    repo_path: String,
+    rev: Option<String>,
+    pattern: String,
+    options: Option<GrepOptions>,
+) -> Result<GrepResult, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    grep::grep_tree(&path, rev.as_deref(), &pattern, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pickaxe_search(
+    repo_path: String,
+    rev: Option<String>,
+    needle: String,
+    options: Option<PickaxeOptions>,
+) -> Result<Vec<PickaxeHit>, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    grep::pickaxe_search(&path, rev.as_deref(), &needle, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn verify_commit_signature(
+    repo_path: String,
+    rev: String,
+    options: Option<VerifyOptions>,
+) -> Result<SignatureVerification, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    verify::verify_commit(&path, &rev, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn verify_tag_signature(
+    repo_path: String,
+    tag: String,
+    options: Option<VerifyOptions>,
+) -> Result<SignatureVerification, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    verify::verify_tag(&path, &tag, &options).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn list_refs(repo_path: String) -> Result<Vec<RefInfo>, String> {
     let path = PathBuf::from(&repo_path);
     refs::list_refs(&path).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn list_branch_status(
+    repo_path: String,
+    options: Option<BranchStatusOptions>,
+) -> Result<Vec<BranchStatus>, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    branches::list_branch_status(&path, &options).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_commit_dag(
     repo_path: String,
@@ -66,15 +230,128 @@ pub async fn get_diff_text(
     repo_path: String,
     old_oid: String,
     new_oid: String,
+    options: Option<DiffOptions>,
+) -> Result<String, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    diff::generate_unified_diff_text(&path, &old_oid, &new_oid, &options)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_commit_diff_text(
+    repo_path: String,
+    oid: String,
+    options: Option<DiffOptions>,
+) -> Result<String, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    diff::diff_commit_to_parent_text(&path, &oid, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_diff(
+    repo_path: String,
+    old_oid: String,
+    new_oid: String,
+    options: Option<DiffOptions>,
+) -> Result<Vec<FileDiff>, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    diff::generate_file_diffs(&path, &old_oid, &new_oid, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_commit_diff(
+    repo_path: String,
+    oid: String,
+    options: Option<DiffOptions>,
+) -> Result<Vec<FileDiff>, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    diff::diff_commit_to_parent(&path, &oid, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_changed_files(
+    repo_path: String,
+    old_oid: Option<String>,
+    new_oid: String,
+    options: Option<DiffOptions>,
+) -> Result<Vec<ChangedFile>, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    diff::list_changed_files(&path, old_oid.as_deref(), &new_oid, &options)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_path_diff(
+    repo_path: String,
+    old_oid: Option<String>,
+    new_oid: String,
+    pathspecs: Vec<String>,
+    options: Option<DiffOptions>,
+) -> Result<Vec<FileDiff>, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    diff::diff_paths(&path, old_oid.as_deref(), &new_oid, &pathspecs, &options)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_commit_parent_diffs(
+    repo_path: String,
+    oid: String,
+    options: Option<DiffOptions>,
+) -> Result<Vec<ParentDiff>, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    combined::diff_commit_to_each_parent(&path, &oid, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_combined_diff(
+    repo_path: String,
+    oid: String,
+    options: Option<DiffOptions>,
+) -> Result<Vec<CombinedFileDiff>, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    combined::combined_diff(&path, &oid, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_combined_diff_text(
+    repo_path: String,
+    oid: String,
+    options: Option<DiffOptions>,
 ) -> Result<String, String> {
     let path = PathBuf::from(&repo_path);
-    diff::generate_unified_diff_text(&path, &old_oid, &new_oid).map_err(|e| e.to_string())
+    let options = options.unwrap_or_default();
+    combined::combined_diff_text(&path, &oid, &options).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn get_commit_diff_text(repo_path: String, oid: String) -> Result<String, String> {
+pub async fn get_commit_stats(
+    repo_path: String,
+    oid: String,
+    options: Option<DiffOptions>,
+) -> Result<CommitStats, String> {
+    let path = PathBuf::from(&repo_path);
+    let options = options.unwrap_or_default();
+    stats::get_commit_stats(&path, &oid, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_commit_stats_batch(
+    repo_path: String,
+    oids: Vec<String>,
+    options: Option<DiffOptions>,
+) -> Result<Vec<CommitStats>, String> {
     let path = PathBuf::from(&repo_path);
-    diff::diff_commit_to_parent(&path, &oid).map_err(|e| e.to_string())
+    let options = options.unwrap_or_default();
+    stats::get_commit_stats_batch(&path, &oids, &options).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
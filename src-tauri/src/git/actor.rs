@@ -0,0 +1,54 @@
+use gix::actor::SignatureRef;
+
+/// An author or committer line of a commit, decoded by gix rather than parsed
+/// back out of its display string.
+pub struct Signature {
+    pub name: String,
+    pub email: String,
+    pub timestamp: i64,
+    /// Seconds east of UTC, as recorded by whoever made the commit.
+    pub utc_offset: i32,
+}
+
+/// A malformed date falls back to the leading seconds with a zero offset, the
+/// same leniency `git log` shows for broken timezones.
+pub fn decode_signature(signature: SignatureRef<'_>) -> Signature {
+    let signature = signature.trim();
+    let (timestamp, utc_offset) = match signature.time() {
+        Ok(time) => (time.seconds, time.offset),
+        Err(_) => (signature.seconds(), 0),
+    };
+    Signature {
+        name: signature.name.to_string(),
+        email: signature.email.to_string(),
+        timestamp,
+        utc_offset,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_signature() {
+        let signature = SignatureRef {
+            name: " Test User ".into(),
+            email: "test@example.com".into(),
+            time: "1234567890 -0530",
+        };
+        let decoded = decode_signature(signature);
+        assert_eq!(decoded.name, "Test User");
+        assert_eq!(decoded.email, "test@example.com");
+        assert_eq!(decoded.timestamp, 1234567890);
+        assert_eq!(decoded.utc_offset, -(5 * 3600 + 30 * 60));
+
+        let broken = SignatureRef {
+            name: "Test".into(),
+            email: "test@example.com".into(),
+            time: "1234567890 +99zz",
+        };
+        let decoded = decode_signature(broken);
+        assert_eq!((decoded.timestamp, decoded.utc_offset), (1234567890, 0));
+    }
+}
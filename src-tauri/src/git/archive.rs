@@ -0,0 +1,493 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use gix::attrs::State;
+use gix::object::tree::{EntryKind, EntryMode};
+use gix::ObjectId;
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::error::AppError;
+use crate::git::attributes::TreeAttributes;
+use crate::git::diff::{commit_tree_id, decode_tree, lookup_path, read_blob};
+use crate::git::log::parse_commit_from_repo;
+use crate::git::repository::open_bare_repo;
+use crate::git::revision::resolve_commit;
+use crate::progress::{emit_progress, emit_progress_detail};
+use crate::types::{ArchiveFormat, ArchiveOptions, ArchiveResult};
+
+const PROGRESS_EVENT: &str = "archive-progress";
+
+struct ArchiveEntry {
+    path: String,
+    mode: EntryMode,
+    oid: ObjectId,
+}
+
+/// Like `git archive`: writes the tree of `rev` (HEAD by default), or the part
+/// of it under `options.path`, to `output`. Paths with the `export-ignore`
+/// attribute are left out, and every entry is stamped with the commit time.
+pub fn export_archive(
+    repo_path: &Path,
+    rev: Option<&str>,
+    output: &Path,
+    options: &ArchiveOptions,
+    app_handle: Option<&AppHandle>,
+) -> Result<ArchiveResult, AppError> {
+    emit_progress(app_handle, PROGRESS_EVENT, "archive.preparing");
+    let repo = open_bare_repo(repo_path)?;
+    let oid = resolve_commit(&repo, rev.unwrap_or("HEAD"))?;
+    let commit = parse_commit_from_repo(&repo, oid)?;
+    let entries = collect_entries(&repo, oid, options.path.trim_matches('/'))?;
+
+    let prefix = match options.prefix.trim_start_matches('/') {
+        "" => String::new(),
+        prefix if prefix.ends_with('/') => prefix.to_string(),
+        prefix => format!("{prefix}/"),
+    };
+    let mut writer = ArchiveWriter::create(
+        output,
+        options.format,
+        commit.committer_timestamp,
+        commit.committer_utc_offset,
+    )?;
+
+    let result = (|| {
+        let file_count = entries.iter().filter(|e| !e.mode.is_tree()).count();
+        let mut written = 0;
+        for entry in &entries {
+            let name = format!("{prefix}{}", entry.path);
+            if entry.mode.is_tree() {
+                writer.add_directory(&name)?;
+                continue;
+            }
+            let data = read_blob(&repo, entry.oid)?;
+            writer.add_file(&name, entry.mode, &data)?;
+            written += 1;
+            if written % 100 == 0 || written == file_count {
+                emit_progress_detail(
+                    app_handle,
+                    PROGRESS_EVENT,
+                    "archive.writing",
+                    format!("{written}/{file_count}"),
+                );
+            }
+        }
+        writer.finish()?;
+        Ok(file_count)
+    })();
+
+    let file_count = match result {
+        Ok(file_count) => file_count,
+        Err(e) => {
+            let _ = std::fs::remove_file(output);
+            return Err(e);
+        }
+    };
+    emit_progress(app_handle, PROGRESS_EVENT, "archive.complete");
+
+    Ok(ArchiveResult {
+        commit_oid: oid.to_string(),
+        output_path: output.to_string_lossy().to_string(),
+        file_count,
+        size: std::fs::metadata(output)?.len(),
+    })
+}
+
+/// Lists the entries to export in tree order, directories before their
+/// contents. The directories leading to `path` are included so the archive
+/// extracts to the same layout as the repository. Submodules are skipped.
+fn collect_entries(
+    repo: &gix::Repository,
+    oid: ObjectId,
+    path: &str,
+) -> Result<Vec<ArchiveEntry>, AppError> {
+    let root_id = commit_tree_id(repo, oid)?;
+    let mut attributes = TreeAttributes::new(repo, root_id)?;
+    let mut entries = Vec::new();
+    if path.is_empty() {
+        walk_tree(repo, &mut attributes, root_id, "", &mut entries)?;
+        return Ok(entries);
+    }
+
+    let (mode, id) = lookup_path(repo, root_id, path)?
+        .ok_or_else(|| AppError::Git(format!("'{path}' does not exist in {oid}")))?;
+    if attributes.state(path, "export-ignore")? == State::Set {
+        return Ok(entries);
+    }
+
+    let mut dir = String::new();
+    for component in path.split('/').collect::<Vec<_>>().split_last().unwrap().1 {
+        dir.push_str(component);
+        dir.push('/');
+        entries.push(ArchiveEntry {
+            path: dir.clone(),
+            mode: EntryKind::Tree.into(),
+            oid: ObjectId::null(repo.object_hash()),
+        });
+    }
+    match mode.kind() {
+        EntryKind::Tree => {
+            entries.push(ArchiveEntry {
+                path: format!("{path}/"),
+                mode,
+                oid: id,
+            });
+            walk_tree(repo, &mut attributes, id, path, &mut entries)?;
+        }
+        EntryKind::Commit => {}
+        _ => entries.push(ArchiveEntry {
+            path: path.to_string(),
+            mode,
+            oid: id,
+        }),
+    }
+    Ok(entries)
+}
+
+fn walk_tree(
+    repo: &gix::Repository,
+    attributes: &mut TreeAttributes,
+    tree_id: ObjectId,
+    dir: &str,
+    entries: &mut Vec<ArchiveEntry>,
+) -> Result<(), AppError> {
+    for (name, mode, oid) in decode_tree(repo, tree_id)? {
+        let path = if dir.is_empty() {
+            name
+        } else {
+            format!("{dir}/{name}")
+        };
+        if mode.is_commit() || attributes.state(&path, "export-ignore")? == State::Set {
+            continue;
+        }
+        if mode.is_tree() {
+            entries.push(ArchiveEntry {
+                path: format!("{path}/"),
+                mode,
+                oid,
+            });
+            walk_tree(repo, attributes, oid, &path, entries)?;
+        } else {
+            entries.push(ArchiveEntry { path, mode, oid });
+        }
+    }
+    Ok(())
+}
+
+enum ArchiveWriter {
+    Tar(tar::Builder<BufWriter<File>>, u64),
+    TarGz(tar::Builder<GzEncoder<BufWriter<File>>>, u64),
+    Zip(ZipWriter<BufWriter<File>>, zip::DateTime),
+}
+
+impl ArchiveWriter {
+    fn create(
+        output: &Path,
+        format: ArchiveFormat,
+        timestamp: i64,
+        utc_offset: i32,
+    ) -> Result<Self, AppError> {
+        let file = BufWriter::new(File::create(output)?);
+        let mtime = timestamp.max(0) as u64;
+        Ok(match format {
+            ArchiveFormat::Tar => Self::Tar(tar::Builder::new(file), mtime),
+            ArchiveFormat::TarGz => Self::TarGz(
+                tar::Builder::new(GzEncoder::new(file, Compression::default())),
+                mtime,
+            ),
+            // Zip stores local time, so the commit's own offset is applied.
+            ArchiveFormat::Zip => Self::Zip(
+                ZipWriter::new(file),
+                zip_time(timestamp + i64::from(utc_offset)),
+            ),
+        })
+    }
+
+    fn add_directory(&mut self, name: &str) -> Result<(), AppError> {
+        match self {
+            Self::Tar(builder, mtime) => append_tar(builder, *mtime, name, 0o040775, b""),
+            Self::TarGz(builder, mtime) => append_tar(builder, *mtime, name, 0o040775, b""),
+            Self::Zip(writer, time) => writer
+                .add_directory(name, zip_options(*time, 0o755))
+                .map_err(|e| AppError::Git(e.to_string())),
+        }
+    }
+
+    fn add_file(&mut self, name: &str, mode: EntryMode, data: &[u8]) -> Result<(), AppError> {
+        let tar_mode = match mode.kind() {
+            EntryKind::Link => 0o120777,
+            EntryKind::BlobExecutable => 0o100775,
+            _ => 0o100664,
+        };
+        match self {
+            Self::Tar(builder, mtime) => append_tar(builder, *mtime, name, tar_mode, data),
+            Self::TarGz(builder, mtime) => append_tar(builder, *mtime, name, tar_mode, data),
+            Self::Zip(writer, time) => {
+                let result = if mode.is_link() {
+                    writer.add_symlink(
+                        name,
+                        String::from_utf8_lossy(data),
+                        zip_options(*time, 0o777),
+                    )
+                } else {
+                    let permissions = if mode.is_executable() { 0o755 } else { 0o644 };
+                    writer
+                        .start_file(name, zip_options(*time, permissions))
+                        .and_then(|()| writer.write_all(data).map_err(Into::into))
+                };
+                result.map_err(|e| AppError::Git(e.to_string()))
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), AppError> {
+        match self {
+            Self::Tar(builder, _) => {
+                builder.into_inner()?.flush()?;
+            }
+            Self::TarGz(builder, _) => {
+                builder.into_inner()?.finish()?.flush()?;
+            }
+            Self::Zip(writer, _) => {
+                writer
+                    .finish()
+                    .map_err(|e| AppError::Git(e.to_string()))?
+                    .flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes a ustar entry the way `git archive` does: mode bits from the tree
+/// with the umask applied, no owner, and symlink targets taken from the blob.
+fn append_tar<W: Write>(
+    builder: &mut tar::Builder<W>,
+    mtime: u64,
+    name: &str,
+    mode: u32,
+    data: &[u8],
+) -> Result<(), AppError> {
+    let mut header = tar::Header::new_ustar();
+    header.set_mtime(mtime);
+    header.set_mode(mode & 0o7777);
+    match mode & 0o170000 {
+        0o040000 => {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_size(0);
+            builder.append_data(&mut header, name, std::io::empty())?;
+        }
+        0o120000 => {
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            builder.append_link(&mut header, name, String::from_utf8_lossy(data).as_ref())?;
+        }
+        _ => {
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_size(data.len() as u64);
+            builder.append_data(&mut header, name, data)?;
+        }
+    }
+    Ok(())
+}
+
+fn zip_options(time: zip::DateTime, permissions: u32) -> SimpleFileOptions {
+    SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(time)
+        .unix_permissions(permissions)
+}
+
+/// Converts seconds since the epoch to a DOS timestamp, clamped to the years
+/// zip can represent.
+fn zip_time(seconds: i64) -> zip::DateTime {
+    let days = seconds.div_euclid(86_400);
+    let secs = seconds.rem_euclid(86_400);
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    if year < 1980 {
+        return zip::DateTime::default();
+    }
+    zip::DateTime::from_date_and_time(
+        year.min(2107) as u16,
+        month as u8,
+        day as u8,
+        (secs / 3600) as u8,
+        (secs % 3600 / 60) as u8,
+        (secs % 60) as u8,
+    )
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_repo_for_archive() -> (TempDir, TempDir) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+        };
+        git(&["init"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+
+        std::fs::create_dir_all(work.join("src/generated")).unwrap();
+        std::fs::write(work.join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        std::fs::write(work.join("src/generated/out.rs"), "// generated\n").unwrap();
+        std::fs::write(work.join("build.sh"), "#!/bin/sh\n").unwrap();
+        std::fs::write(work.join("secret.txt"), "hidden\n").unwrap();
+        std::fs::write(
+            work.join(".gitattributes"),
+            "secret.txt export-ignore\nsrc/generated export-ignore\n",
+        )
+        .unwrap();
+        std::os::unix::fs::symlink("src/lib.rs", work.join("link")).unwrap();
+        git(&["add", "."]);
+        git(&["update-index", "--chmod=+x", "build.sh"]);
+        git(&["commit", "-m", "first"]);
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        (work_dir, bare_dir)
+    }
+
+    fn tar_entries<R: Read>(reader: R) -> Vec<(String, u32)> {
+        let mut archive = tar::Archive::new(reader);
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().to_string();
+                (path, entry.header().mode().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_export_tar() {
+        let (_work, bare) = create_repo_for_archive();
+        let out = TempDir::new().unwrap();
+        let output = out.path().join("snapshot.tar");
+        let options = ArchiveOptions {
+            prefix: "snapshot".to_string(),
+            ..ArchiveOptions::default()
+        };
+        let result = export_archive(bare.path(), None, &output, &options, None).unwrap();
+        assert_eq!(result.file_count, 4);
+        assert_eq!(result.size, std::fs::metadata(&output).unwrap().len());
+
+        let entries = tar_entries(File::open(&output).unwrap());
+        assert_eq!(
+            entries,
+            vec![
+                ("snapshot/.gitattributes".to_string(), 0o664),
+                ("snapshot/build.sh".to_string(), 0o775),
+                ("snapshot/link".to_string(), 0o777),
+                ("snapshot/src/".to_string(), 0o775),
+                ("snapshot/src/lib.rs".to_string(), 0o664),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_subpath_tar_gz() {
+        let (_work, bare) = create_repo_for_archive();
+        let out = TempDir::new().unwrap();
+        let output = out.path().join("src.tar.gz");
+        let options = ArchiveOptions {
+            format: ArchiveFormat::TarGz,
+            path: "src/".to_string(),
+            ..ArchiveOptions::default()
+        };
+        let result = export_archive(bare.path(), None, &output, &options, None).unwrap();
+        assert_eq!(result.file_count, 1);
+
+        let entries = tar_entries(flate2::read::GzDecoder::new(File::open(&output).unwrap()));
+        let paths: Vec<&str> = entries.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, vec!["src/", "src/lib.rs"]);
+
+        let missing = ArchiveOptions {
+            path: "missing".to_string(),
+            ..ArchiveOptions::default()
+        };
+        assert!(export_archive(bare.path(), None, &output, &missing, None).is_err());
+    }
+
+    #[test]
+    fn test_export_zip() {
+        let (_work, bare) = create_repo_for_archive();
+        let out = TempDir::new().unwrap();
+        let output = out.path().join("snapshot.zip");
+        let options = ArchiveOptions {
+            format: ArchiveFormat::Zip,
+            ..ArchiveOptions::default()
+        };
+        export_archive(bare.path(), None, &output, &options, None).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(String::from).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![".gitattributes", "build.sh", "link", "src/", "src/lib.rs"]
+        );
+        let mut lib = String::new();
+        archive
+            .by_name("src/lib.rs")
+            .unwrap()
+            .read_to_string(&mut lib)
+            .unwrap();
+        assert_eq!(lib, "pub fn lib() {}\n");
+        assert_eq!(
+            archive.by_name("build.sh").unwrap().unix_mode().unwrap() & 0o777,
+            0o755
+        );
+    }
+
+    #[test]
+    fn test_zip_time() {
+        let time = zip_time(1_700_000_000);
+        assert_eq!(
+            (
+                time.year(),
+                time.month(),
+                time.day(),
+                time.hour(),
+                time.minute()
+            ),
+            (2023, 11, 14, 22, 13)
+        );
+        assert_eq!(zip_time(0), zip::DateTime::default());
+    }
+}
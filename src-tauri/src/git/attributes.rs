@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use gix::attrs::search::{MetadataCollection, Outcome};
+use gix::attrs::{Search, State};
+use gix::glob::pattern::Case;
+use gix::ObjectId;
+
+use crate::error::AppError;
+use crate::git::diff::lookup_path;
+
+/// Resolves git attributes for paths of a tree without a worktree, reading
+/// `.gitattributes` blobs from the tree itself plus `$GIT_DIR/info/attributes`.
+pub struct TreeAttributes<'repo> {
+    repo: &'repo gix::Repository,
+    tree_id: ObjectId,
+    info: Search,
+    tree: Search,
+    collection: MetadataCollection,
+    loaded_dirs: HashSet<String>,
+}
+
+impl<'repo> TreeAttributes<'repo> {
+    pub fn new(repo: &'repo gix::Repository, tree_id: ObjectId) -> Result<Self, AppError> {
+        let mut collection = MetadataCollection::default();
+        let mut buf = Vec::new();
+        let mut info =
+            Search::new_globals(std::iter::empty::<PathBuf>(), &mut buf, &mut collection)?;
+        info.add_patterns_file(
+            repo.git_dir().join("info").join("attributes"),
+            true,
+            None,
+            &mut buf,
+            &mut collection,
+            true,
+        )?;
+
+        Ok(Self {
+            repo,
+            tree_id,
+            info,
+            tree: Search::default(),
+            collection,
+            loaded_dirs: HashSet::new(),
+        })
+    }
+
+    pub fn state(&mut self, path: &str, attribute: &str) -> Result<State, AppError> {
+        self.load_parent_dirs(path)?;
+
+        let mut outcome = Outcome::default();
+        outcome.initialize_with_selection(&self.collection, [attribute]);
+        // Earlier matches win, so the repository-local file takes precedence over the tree.
+        self.info.pattern_matching_relative_path(
+            path.into(),
+            Case::Sensitive,
+            Some(false),
+            &mut outcome,
+        );
+        self.tree.pattern_matching_relative_path(
+            path.into(),
+            Case::Sensitive,
+            Some(false),
+            &mut outcome,
+        );
+
+        let state = outcome
+            .iter_selected()
+            .next()
+            .map(|m| m.assignment.state.to_owned())
+            .unwrap_or(State::Unspecified);
+        Ok(state)
+    }
+
+    /// Attribute files are loaded from the root downwards so that deeper files,
+    /// which are added later, take precedence when searching.
+    fn load_parent_dirs(&mut self, path: &str) -> Result<(), AppError> {
+        let mut dir = String::new();
+        let components: Vec<&str> = path.split('/').collect();
+        for (i, component) in components[..components.len() - 1].iter().enumerate() {
+            self.load_dir(&dir)?;
+            if i > 0 {
+                dir.push('/');
+            }
+            dir.push_str(component);
+        }
+        self.load_dir(&dir)
+    }
+
+    fn load_dir(&mut self, dir: &str) -> Result<(), AppError> {
+        if !self.loaded_dirs.insert(dir.to_string()) {
+            return Ok(());
+        }
+
+        let file_path = if dir.is_empty() {
+            ".gitattributes".to_string()
+        } else {
+            format!("{dir}/.gitattributes")
+        };
+
+        let entry = lookup_path(self.repo, self.tree_id, &file_path)?;
+        let Some((_, id)) = entry.filter(|(mode, _)| mode.is_blob()) else {
+            return Ok(());
+        };
+
+        let blob = self
+            .repo
+            .find_object(id)
+            .map_err(|e| AppError::Git(e.to_string()))?;
+        // Like git, macros may only be defined at the top level.
+        self.tree.add_patterns_buffer(
+            &blob.data,
+            PathBuf::from(file_path),
+            Some(Path::new("")),
+            &mut self.collection,
+            dir.is_empty(),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::repository::open_bare_repo;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_repo_with_attributes() -> (TempDir, TempDir) {
+        let work_dir = TempDir::new().unwrap();
+        let bare_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+        };
+        git(&["init"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+
+        std::fs::create_dir(work.join("assets")).unwrap();
+        std::fs::write(work.join(".gitattributes"), "*.dat binary\n*.txt diff\n").unwrap();
+        std::fs::write(work.join("assets/.gitattributes"), "*.txt -diff\n").unwrap();
+        std::fs::write(work.join("assets/a.txt"), "a").unwrap();
+        std::fs::write(work.join("b.txt"), "b").unwrap();
+        std::fs::write(work.join("c.dat"), "c").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "attributes"]);
+
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+
+        (work_dir, bare_dir)
+    }
+
+    #[test]
+    fn test_tree_attributes_precedence_and_macros() {
+        let (_work, bare) = create_repo_with_attributes();
+        let repo = open_bare_repo(bare.path()).unwrap();
+        let tree_id = repo.head_commit().unwrap().tree_id().unwrap().detach();
+        let mut attributes = TreeAttributes::new(&repo, tree_id).unwrap();
+
+        assert!(matches!(
+            attributes.state("assets/a.txt", "diff").unwrap(),
+            State::Unset
+        ));
+        assert!(matches!(
+            attributes.state("b.txt", "diff").unwrap(),
+            State::Set
+        ));
+        assert!(matches!(
+            attributes.state("c.dat", "diff").unwrap(),
+            State::Unset
+        ));
+        assert!(matches!(
+            attributes.state("d.rs", "diff").unwrap(),
+            State::Unspecified
+        ));
+    }
+}
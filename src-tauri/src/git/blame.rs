@@ -0,0 +1,345 @@
+use std::collections::{BinaryHeap, HashMap};
+use std::path::Path;
+
+use gix::ObjectId;
+use similar::DiffOp;
+
+use crate::error::AppError;
+use crate::git::diff::{blob_at, diff_lines, read_blob_text};
+use crate::git::history::find_rename_source;
+use crate::git::log::parse_commit_from_repo;
+use crate::git::repository::open_bare_repo;
+use crate::git::revision::resolve_commit;
+use crate::types::{BlameHunk, BlameOptions, CommitInfo, DiffOptions, FileBlame, FileStatus};
+
+/// Lines of the blamed file still waiting to be attributed, as
+/// `(line in this commit's version, line in the final file)`.
+struct Pending {
+    path: String,
+    lines: Vec<(usize, usize)>,
+}
+
+#[derive(Clone)]
+struct Attribution {
+    commit: ObjectId,
+    orig_line: usize,
+    orig_path: String,
+    previous: Option<(ObjectId, String)>,
+}
+
+struct BlameState<'repo> {
+    repo: &'repo gix::Repository,
+    commits: HashMap<ObjectId, CommitInfo>,
+    texts: HashMap<ObjectId, String>,
+}
+
+impl<'repo> BlameState<'repo> {
+    fn commit(&mut self, oid: ObjectId) -> Result<&CommitInfo, AppError> {
+        if !self.commits.contains_key(&oid) {
+            let info = parse_commit_from_repo(self.repo, oid)?;
+            self.commits.insert(oid, info);
+        }
+        Ok(&self.commits[&oid])
+    }
+
+    fn text(&mut self, blob: ObjectId) -> Result<&str, AppError> {
+        if !self.texts.contains_key(&blob) {
+            let text = read_blob_text(self.repo, blob)?;
+            self.texts.insert(blob, text);
+        }
+        Ok(self.texts[&blob].as_str())
+    }
+}
+
+/// Attributes every line of `file_path` at `rev` (HEAD by default) to the commit
+/// that last changed it. Commits are visited newest first, and lines a commit
+/// shares with a parent are handed on to that parent, following renames.
+pub fn blame_file(
+    repo_path: &Path,
+    file_path: &str,
+    rev: Option<&str>,
+    options: &BlameOptions,
+) -> Result<FileBlame, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let start = match rev {
+        Some(spec) => resolve_commit(&repo, spec)?,
+        None => {
+            repo.head_commit()
+                .map_err(|e| AppError::Git(e.to_string()))?
+                .id
+        }
+    };
+    let path = file_path.trim_matches('/').to_string();
+    let blob = blob_at(&repo, start, &path)?
+        .ok_or_else(|| AppError::Git(format!("'{path}' does not exist in {start}")))?;
+
+    let mut state = BlameState {
+        repo: &repo,
+        commits: HashMap::new(),
+        texts: HashMap::new(),
+    };
+    let lines: Vec<String> = state
+        .text(blob)?
+        .split_inclusive('\n')
+        .map(|l| l.strip_suffix('\n').unwrap_or(l).to_string())
+        .collect();
+    let diff_options = DiffOptions {
+        ignore_whitespace: options.ignore_whitespace,
+        ..DiffOptions::default()
+    };
+
+    let mut attributions: Vec<Option<Attribution>> = vec![None; lines.len()];
+    let mut pending: HashMap<ObjectId, Pending> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    pending.insert(
+        start,
+        Pending {
+            path: path.clone(),
+            lines: (0..lines.len()).map(|i| (i, i)).collect(),
+        },
+    );
+    queue.push((state.commit(start)?.committer_timestamp, start));
+
+    while let Some((_, oid)) = queue.pop() {
+        let Some(Pending { path, lines }) = pending.remove(&oid) else {
+            continue;
+        };
+        let mut remaining = lines;
+        let Some(blob) = blob_at(&repo, oid, &path)? else {
+            continue;
+        };
+        let parents = state.commit(oid)?.parent_oids.clone();
+
+        let mut previous = None;
+        for parent in parents {
+            if remaining.is_empty() {
+                break;
+            }
+            let parent = ObjectId::from_hex(parent.as_bytes())
+                .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?;
+            let parent_path = match blob_at(&repo, parent, &path)? {
+                Some(_) => path.clone(),
+                None => match find_rename_source(&repo, parent, oid, &path)? {
+                    (FileStatus::Renamed | FileStatus::Copied, Some(old_path)) => old_path,
+                    _ => continue,
+                },
+            };
+            let Some(parent_blob) = blob_at(&repo, parent, &parent_path)? else {
+                continue;
+            };
+            previous.get_or_insert_with(|| (parent, parent_path.clone()));
+
+            let passed: Vec<(usize, usize)>;
+            if parent_blob == blob {
+                passed = std::mem::take(&mut remaining);
+            } else {
+                let parent_text = state.text(parent_blob)?.to_string();
+                let line_map = map_unchanged_lines(&parent_text, state.text(blob)?, &diff_options);
+                let (kept, rest): (Vec<_>, Vec<_>) = remaining
+                    .into_iter()
+                    .partition(|(line, _)| line_map.contains_key(line));
+                passed = kept
+                    .into_iter()
+                    .map(|(line, final_line)| (line_map[&line], final_line))
+                    .collect();
+                remaining = rest;
+            }
+
+            if !passed.is_empty() {
+                let timestamp = state.commit(parent)?.committer_timestamp;
+                pending
+                    .entry(parent)
+                    .or_insert_with(|| Pending {
+                        path: parent_path,
+                        lines: Vec::new(),
+                    })
+                    .lines
+                    .extend(passed);
+                queue.push((timestamp, parent));
+            }
+        }
+
+        for (line, final_line) in remaining {
+            attributions[final_line] = Some(Attribution {
+                commit: oid,
+                orig_line: line,
+                orig_path: path.clone(),
+                previous: previous.clone(),
+            });
+        }
+    }
+
+    let hunks = build_blame_hunks(&mut state, &attributions)?;
+    Ok(FileBlame {
+        commit_oid: start.to_string(),
+        path,
+        lines,
+        hunks,
+    })
+}
+
+/// Maps line indexes of `new` that are unchanged from `old` to their index in `old`.
+fn map_unchanged_lines(old: &str, new: &str, options: &DiffOptions) -> HashMap<usize, usize> {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+
+    let mut map = HashMap::new();
+    for op in diff_lines(&old_lines, &new_lines, options) {
+        if let DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            map.extend((0..len).map(|i| (new_index + i, old_index + i)));
+        }
+    }
+    map
+}
+
+fn build_blame_hunks(
+    state: &mut BlameState,
+    attributions: &[Option<Attribution>],
+) -> Result<Vec<BlameHunk>, AppError> {
+    let mut hunks: Vec<BlameHunk> = Vec::new();
+    let mut last: Option<&Attribution> = None;
+
+    for (index, attribution) in attributions.iter().enumerate() {
+        let Some(attribution) = attribution else {
+            last = None;
+            continue;
+        };
+        let continues = last.is_some_and(|prev| {
+            prev.commit == attribution.commit
+                && prev.orig_path == attribution.orig_path
+                && prev.orig_line + 1 == attribution.orig_line
+        });
+        last = Some(attribution);
+        if continues {
+            if let Some(hunk) = hunks.last_mut() {
+                hunk.line_count += 1;
+            }
+            continue;
+        }
+
+        let commit = state.commit(attribution.commit)?;
+        let (previous_oid, previous_path) = match &attribution.previous {
+            Some((oid, path)) => (Some(oid.to_string()), Some(path.clone())),
+            None => (None, None),
+        };
+        hunks.push(BlameHunk {
+            start_line: index + 1,
+            line_count: 1,
+            orig_start_line: attribution.orig_line + 1,
+            orig_path: attribution.orig_path.clone(),
+            commit_oid: commit.oid.clone(),
+            author_name: commit.author_name.clone(),
+            author_email: commit.author_email.clone(),
+            timestamp: commit.timestamp,
+            summary: commit.subject.clone(),
+            previous_oid,
+            previous_path,
+        });
+    }
+
+    Ok(hunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_repo_for_blame() -> (TempDir, TempDir, Vec<String>) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+
+        let mut commits = Vec::new();
+        std::fs::write(work.join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "first"]);
+        commits.push(git(&["rev-parse", "HEAD"]));
+
+        std::fs::write(work.join("a.txt"), "one\n  two\nthree\nfour\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "indent and append"]);
+        commits.push(git(&["rev-parse", "HEAD"]));
+
+        git(&["mv", "a.txt", "b.txt"]);
+        std::fs::write(work.join("b.txt"), "ONE\n  two\nthree\nfour\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "rename and shout"]);
+        commits.push(git(&["rev-parse", "HEAD"]));
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        (work_dir, bare_dir, commits)
+    }
+
+    fn line_commits(blame: &FileBlame) -> Vec<&str> {
+        blame
+            .hunks
+            .iter()
+            .flat_map(|h| std::iter::repeat_n(h.commit_oid.as_str(), h.line_count))
+            .collect()
+    }
+
+    #[test]
+    fn test_blame_file_follows_renames() {
+        let (_work, bare, commits) = create_repo_for_blame();
+        let blame = blame_file(bare.path(), "b.txt", None, &BlameOptions::default()).unwrap();
+
+        assert_eq!(blame.lines, vec!["ONE", "  two", "three", "four"]);
+        assert_eq!(
+            line_commits(&blame),
+            vec![&commits[2], &commits[1], &commits[0], &commits[1]]
+        );
+        assert_eq!(blame.hunks[2].orig_path, "a.txt");
+        assert_eq!(blame.hunks[2].orig_start_line, 3);
+        assert_eq!(blame.hunks[0].previous_oid.as_ref(), Some(&commits[1]));
+        assert_eq!(blame.hunks[0].previous_path.as_deref(), Some("a.txt"));
+    }
+
+    #[test]
+    fn test_blame_file_ignore_whitespace_and_reblame() {
+        let (_work, bare, commits) = create_repo_for_blame();
+        let options = BlameOptions {
+            ignore_whitespace: true,
+        };
+        let blame = blame_file(bare.path(), "b.txt", None, &options).unwrap();
+        assert_eq!(
+            line_commits(&blame),
+            vec![&commits[2], &commits[0], &commits[0], &commits[1]]
+        );
+
+        let first = &blame.hunks[0];
+        let parent = blame_file(
+            bare.path(),
+            first.previous_path.as_deref().unwrap(),
+            first.previous_oid.as_deref(),
+            &BlameOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(parent.lines[0], "one");
+        assert_eq!(parent.hunks[0].commit_oid, commits[0]);
+    }
+}
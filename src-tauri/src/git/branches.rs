@@ -0,0 +1,261 @@
+use std::path::Path;
+
+use gix::ObjectId;
+
+use crate::error::AppError;
+use crate::git::log::parse_commit_from_repo;
+use crate::git::refs::list_refs;
+use crate::git::repository::open_bare_repo;
+use crate::git::revision::resolve_commit;
+use crate::types::{BranchStatus, BranchStatusOptions, RefType};
+
+/// Lists local branches with how far they have diverged from their base: the
+/// revision in `options.base` if given, otherwise the upstream configured for
+/// the branch when it exists in this repository, otherwise the branch HEAD
+/// points to.
+pub fn list_branch_status(
+    repo_path: &Path,
+    options: &BranchStatusOptions,
+) -> Result<Vec<BranchStatus>, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let refs = list_refs(repo_path)?;
+    let find_oid = |full_name: &str| {
+        refs.iter()
+            .find(|r| r.full_name == full_name)
+            .and_then(|r| ObjectId::from_hex(r.target_oid.as_bytes()).ok())
+    };
+
+    // A base naming a ref is reported by its full name, so that the branch it
+    // names is recognised as the base itself.
+    let explicit_base = match options.base.as_deref() {
+        Some(base) => {
+            let name = repo
+                .find_reference(base)
+                .map(|r| r.name().as_bstr().to_string())
+                .unwrap_or_else(|_| base.to_string());
+            Some((name, resolve_commit(&repo, base)?))
+        }
+        None => None,
+    };
+    let default_base = refs
+        .iter()
+        .find(|r| r.is_head)
+        .and_then(|r| Some((r.full_name.clone(), find_oid(&r.full_name)?)));
+
+    let mut branches = Vec::new();
+    for branch in refs.iter().filter(|r| r.ref_type == RefType::LocalBranch) {
+        let Ok(oid) = ObjectId::from_hex(branch.target_oid.as_bytes()) else {
+            continue;
+        };
+        let upstream = upstream_of(&repo, &branch.name);
+        let base = explicit_base
+            .clone()
+            .or_else(|| {
+                let upstream = upstream.clone()?;
+                let oid = find_oid(&upstream)?;
+                Some((upstream, oid))
+            })
+            .or_else(|| default_base.clone())
+            .filter(|(name, _)| *name != branch.full_name);
+
+        let (ahead, behind) = match &base {
+            Some((_, base_oid)) => (
+                count_exclusive(&repo, oid, *base_oid)?,
+                count_exclusive(&repo, *base_oid, oid)?,
+            ),
+            None => (0, 0),
+        };
+
+        branches.push(BranchStatus {
+            name: branch.name.clone(),
+            full_name: branch.full_name.clone(),
+            target_oid: branch.target_oid.clone(),
+            is_head: branch.is_head,
+            upstream,
+            base: base.map(|(name, _)| name),
+            ahead,
+            behind,
+            last_commit: parse_commit_from_repo(&repo, oid)?,
+        });
+    }
+    Ok(branches)
+}
+
+/// Resolves `branch.<name>.remote` and `branch.<name>.merge` to the ref that
+/// tracks the upstream locally, mapping it through the remote's fetch refspecs.
+/// A remote of `.` means the upstream is another local branch.
+fn upstream_of(repo: &gix::Repository, branch: &str) -> Option<String> {
+    let config = repo.config_snapshot();
+    let remote = config.string(format!("branch.{branch}.remote"))?;
+    if remote.as_ref() == "." {
+        let merge = config.string(format!("branch.{branch}.merge"))?.to_string();
+        return Some(if merge.starts_with("refs/") {
+            merge
+        } else {
+            format!("refs/heads/{merge}")
+        });
+    }
+
+    let full_name: gix::refs::FullName = format!("refs/heads/{branch}").try_into().ok()?;
+    repo.branch_remote_tracking_ref_name(full_name.as_ref(), gix::remote::Direction::Fetch)?
+        .ok()
+        .map(|name| name.as_bstr().to_string())
+}
+
+/// Counts the commits reachable from `tip` but not from `hidden`, like
+/// `git rev-list --count hidden..tip`.
+fn count_exclusive(
+    repo: &gix::Repository,
+    tip: ObjectId,
+    hidden: ObjectId,
+) -> Result<usize, AppError> {
+    if tip == hidden {
+        return Ok(0);
+    }
+    let walk = repo
+        .rev_walk([tip])
+        .with_hidden([hidden])
+        .all()
+        .map_err(|e| AppError::Git(e.to_string()))?;
+    let mut count = 0;
+    for info in walk {
+        info.map_err(|e| AppError::Git(e.to_string()))?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_repo_with_branches() -> (TempDir, TempDir) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+        };
+        let commit = |file: &str, message: &str| {
+            std::fs::write(work.join(file), message).unwrap();
+            git(&["add", "."]);
+            git(&["commit", "-m", message]);
+        };
+        git(&["init", "-b", "main"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+        commit("a.txt", "base");
+
+        git(&["checkout", "-b", "feature"]);
+        commit("b.txt", "feature one");
+        commit("b.txt", "feature two");
+        git(&["checkout", "main"]);
+        commit("a.txt", "main moves on");
+        git(&["branch", "stale", "HEAD~1"]);
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        (work_dir, bare_dir)
+    }
+
+    fn counts(branches: &[BranchStatus]) -> Vec<(&str, Option<&str>, usize, usize)> {
+        branches
+            .iter()
+            .map(|b| (b.name.as_str(), b.base.as_deref(), b.ahead, b.behind))
+            .collect()
+    }
+
+    #[test]
+    fn test_branch_status_against_default_branch() {
+        let (_work, bare) = create_repo_with_branches();
+        let branches = list_branch_status(bare.path(), &BranchStatusOptions::default()).unwrap();
+        assert_eq!(
+            counts(&branches),
+            vec![
+                ("feature", Some("refs/heads/main"), 2, 1),
+                ("main", None, 0, 0),
+                ("stale", Some("refs/heads/main"), 0, 1),
+            ]
+        );
+        assert!(branches[1].is_head);
+        assert_eq!(branches[0].last_commit.subject, "feature two");
+        assert_eq!(branches[0].last_commit.author_name, "Test");
+    }
+
+    #[test]
+    fn test_branch_status_upstream_and_explicit_base() {
+        let (_work, bare) = create_repo_with_branches();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(bare.path())
+                .args(args)
+                .output()
+                .unwrap();
+        };
+        git(&["config", "branch.feature.remote", "."]);
+        git(&["config", "branch.feature.merge", "refs/heads/stale"]);
+        git(&["config", "branch.stale.remote", "origin"]);
+        git(&["config", "branch.stale.merge", "refs/heads/stale"]);
+        git(&["config", "remote.origin.url", "/nowhere"]);
+        git(&[
+            "config",
+            "remote.origin.fetch",
+            "+refs/heads/*:refs/remotes/origin/*",
+        ]);
+
+        let branches = list_branch_status(bare.path(), &BranchStatusOptions::default()).unwrap();
+        assert_eq!(branches[0].upstream.as_deref(), Some("refs/heads/stale"));
+        // The remote-tracking ref was never fetched, so main is used instead.
+        assert_eq!(
+            branches[2].upstream.as_deref(),
+            Some("refs/remotes/origin/stale")
+        );
+        assert_eq!(
+            counts(&branches),
+            vec![
+                ("feature", Some("refs/heads/stale"), 2, 0),
+                ("main", None, 0, 0),
+                ("stale", Some("refs/heads/main"), 0, 1),
+            ]
+        );
+
+        let options = BranchStatusOptions {
+            base: Some("feature".to_string()),
+        };
+        let branches = list_branch_status(bare.path(), &options).unwrap();
+        assert_eq!(
+            counts(&branches),
+            vec![
+                ("feature", None, 0, 0),
+                ("main", Some("refs/heads/feature"), 1, 2),
+                ("stale", Some("refs/heads/feature"), 0, 2),
+            ]
+        );
+
+        let options = BranchStatusOptions {
+            base: Some("main~1".to_string()),
+        };
+        let branches = list_branch_status(bare.path(), &options).unwrap();
+        assert_eq!(
+            counts(&branches),
+            vec![
+                ("feature", Some("main~1"), 2, 0),
+                ("main", Some("main~1"), 1, 0),
+                ("stale", Some("main~1"), 0, 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,462 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use gix::attrs::State;
+use gix::ObjectId;
+use similar::DiffOp;
+
+use crate::error::AppError;
+use crate::git::attributes::TreeAttributes;
+use crate::git::diff::{
+    collect_changes, diff_lines, diff_trees, format_range, hunk_start, is_binary_content,
+    lookup_path, read_blob,
+};
+use crate::git::repository::open_bare_repo;
+use crate::git::revision::resolve_commit;
+use crate::types::{
+    CombinedDiffLine, CombinedFileDiff, CombinedHunk, DiffLineKind, DiffOptions, ParentDiff,
+};
+
+struct MergeCommit {
+    tree_id: ObjectId,
+    parents: Vec<(ObjectId, ObjectId)>,
+}
+
+fn read_merge_commit(repo: &gix::Repository, oid_str: &str) -> Result<MergeCommit, AppError> {
+    let oid = resolve_commit(repo, oid_str)?;
+
+    let commit = repo
+        .find_object(oid)
+        .map_err(|e| AppError::Git(e.to_string()))?
+        .into_commit();
+    let commit_ref = commit.decode().map_err(|e| AppError::Git(e.to_string()))?;
+
+    let mut parents = Vec::new();
+    for parent_oid in commit_ref.parents() {
+        let parent = repo
+            .find_object(parent_oid)
+            .map_err(|e| AppError::Git(e.to_string()))?
+            .into_commit();
+        let parent_ref = parent.decode().map_err(|e| AppError::Git(e.to_string()))?;
+        parents.push((parent_oid, parent_ref.tree()));
+    }
+
+    Ok(MergeCommit {
+        tree_id: commit_ref.tree(),
+        parents,
+    })
+}
+
+pub fn diff_commit_to_each_parent(
+    repo_path: &Path,
+    oid_str: &str,
+    options: &DiffOptions,
+) -> Result<Vec<ParentDiff>, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let merge = read_merge_commit(&repo, oid_str)?;
+
+    merge
+        .parents
+        .iter()
+        .map(|(parent_oid, parent_tree)| {
+            Ok(ParentDiff {
+                parent_oid: parent_oid.to_string(),
+                files: diff_trees(&repo, Some(*parent_tree), merge.tree_id, options)?,
+            })
+        })
+        .collect()
+}
+
+/// Like `git show --cc`: only paths that differ from every parent are shown,
+/// and only hunks that change lines relative to every parent are kept.
+pub fn combined_diff(
+    repo_path: &Path,
+    oid_str: &str,
+    options: &DiffOptions,
+) -> Result<Vec<CombinedFileDiff>, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let merge = read_merge_commit(&repo, oid_str)?;
+    if merge.parents.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut per_parent: Vec<HashMap<String, Option<ObjectId>>> = Vec::new();
+    for (_, parent_tree) in &merge.parents {
+        let changes = collect_changes(&repo, Some(*parent_tree), merge.tree_id)?;
+        per_parent.push(
+            changes
+                .into_iter()
+                .map(|c| (c.path().to_string(), c.old_id))
+                .collect(),
+        );
+    }
+
+    let candidate_paths: BTreeSet<&String> = per_parent[0].keys().collect();
+    let mut attributes = TreeAttributes::new(&repo, merge.tree_id)?;
+    let mut files = Vec::new();
+
+    for path in candidate_paths {
+        if !per_parent.iter().all(|changes| changes.contains_key(path)) {
+            continue;
+        }
+
+        let new_id = lookup_path(&repo, merge.tree_id, path)?
+            .filter(|(mode, _)| mode.is_blob())
+            .map(|(_, id)| id);
+        let new_data = new_id
+            .map(|id| read_blob(&repo, id))
+            .transpose()?
+            .unwrap_or_default();
+        let mut parent_data = Vec::new();
+        for changes in &per_parent {
+            let data = changes[path]
+                .map(|id| read_blob(&repo, id))
+                .transpose()?
+                .unwrap_or_default();
+            parent_data.push(data);
+        }
+
+        let is_binary = match attributes.state(path, "diff")? {
+            State::Unset => true,
+            State::Set => false,
+            _ => is_binary_content(&new_data) || parent_data.iter().any(|d| is_binary_content(d)),
+        };
+
+        let hunks = if is_binary {
+            Vec::new()
+        } else {
+            let new_text = String::from_utf8_lossy(&new_data);
+            let parent_texts: Vec<_> = parent_data
+                .iter()
+                .map(|d| String::from_utf8_lossy(d))
+                .collect();
+            let parent_refs: Vec<&str> = parent_texts.iter().map(|t| t.as_ref()).collect();
+            build_combined_hunks(&parent_refs, &new_text, options)
+        };
+
+        if is_binary || !hunks.is_empty() {
+            files.push(CombinedFileDiff {
+                path: path.clone(),
+                is_binary,
+                hunks,
+            });
+        }
+    }
+
+    Ok(files)
+}
+
+pub fn combined_diff_text(
+    repo_path: &Path,
+    oid_str: &str,
+    options: &DiffOptions,
+) -> Result<String, AppError> {
+    let files = combined_diff(repo_path, oid_str, options)?;
+    Ok(render_combined_diff(&files))
+}
+
+struct CombinedRow {
+    origins: Vec<DiffLineKind>,
+    old_lines: Vec<Option<usize>>,
+    new_line: Option<usize>,
+    content: String,
+}
+
+fn build_combined_hunks(
+    parents: &[&str],
+    new_content: &str,
+    options: &DiffOptions,
+) -> Vec<CombinedHunk> {
+    let new_lines: Vec<&str> = new_content.split_inclusive('\n').collect();
+    let parent_count = parents.len();
+
+    // For every parent: which result lines are additions, which parent line an
+    // unchanged result line maps to, and which parent lines were lost before
+    // each result line.
+    let mut added = vec![vec![false; new_lines.len()]; parent_count];
+    let mut mapped = vec![vec![None; new_lines.len()]; parent_count];
+    let mut lost: Vec<HashMap<usize, Vec<(usize, &str)>>> = vec![HashMap::new(); parent_count];
+
+    for (p, parent) in parents.iter().enumerate() {
+        let old_lines: Vec<&str> = parent.split_inclusive('\n').collect();
+        for op in diff_lines(&old_lines, &new_lines, options) {
+            let (old_range, new_range) = (op.old_range(), op.new_range());
+            match op {
+                DiffOp::Equal { .. } => {
+                    for (o, n) in old_range.zip(new_range) {
+                        mapped[p][n] = Some(o);
+                    }
+                }
+                _ => {
+                    for n in new_range.clone() {
+                        added[p][n] = true;
+                    }
+                    let removed = lost[p].entry(new_range.start).or_default();
+                    removed.extend(old_range.map(|o| (o, old_lines[o])));
+                }
+            }
+        }
+    }
+
+    let mut rows: Vec<CombinedRow> = Vec::new();
+    for position in 0..=new_lines.len() {
+        let mut lost_rows: Vec<CombinedRow> = Vec::new();
+        for (p, parent_lost) in lost.iter().enumerate() {
+            let Some(removed) = parent_lost.get(&position) else {
+                continue;
+            };
+            // Lines lost from several parents are shown once, like git does.
+            let mut search_from = 0;
+            for (old_index, line) in removed {
+                let content = strip_newline(line);
+                let existing = lost_rows[search_from..]
+                    .iter()
+                    .position(|row| row.content == content && row.old_lines[p].is_none());
+                let row_index = match existing {
+                    Some(offset) => search_from + offset,
+                    None => {
+                        lost_rows.push(CombinedRow {
+                            origins: vec![DiffLineKind::Context; parent_count],
+                            old_lines: vec![None; parent_count],
+                            new_line: None,
+                            content: content.to_string(),
+                        });
+                        lost_rows.len() - 1
+                    }
+                };
+                lost_rows[row_index].origins[p] = DiffLineKind::Deletion;
+                lost_rows[row_index].old_lines[p] = Some(old_index + 1);
+                search_from = row_index + 1;
+            }
+        }
+        rows.extend(lost_rows);
+
+        if let Some(line) = new_lines.get(position) {
+            rows.push(CombinedRow {
+                origins: (0..parent_count)
+                    .map(|p| {
+                        if added[p][position] {
+                            DiffLineKind::Addition
+                        } else {
+                            DiffLineKind::Context
+                        }
+                    })
+                    .collect(),
+                old_lines: (0..parent_count)
+                    .map(|p| mapped[p][position].map(|o| o + 1))
+                    .collect(),
+                new_line: Some(position + 1),
+                content: strip_newline(line).to_string(),
+            });
+        }
+    }
+
+    group_rows(&rows, options.context_lines)
+        .into_iter()
+        .filter(|range| {
+            (0..parent_count).all(|p| {
+                rows[range.clone()]
+                    .iter()
+                    .any(|row| row.origins[p] != DiffLineKind::Context)
+            })
+        })
+        .map(|range| build_combined_hunk(&rows, range, parent_count))
+        .collect()
+}
+
+fn strip_newline(line: &str) -> &str {
+    line.strip_suffix('\n').unwrap_or(line)
+}
+
+fn group_rows(rows: &[CombinedRow], context: usize) -> Vec<std::ops::Range<usize>> {
+    let changed: Vec<usize> = rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| row.origins.iter().any(|o| *o != DiffLineKind::Context))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut groups: Vec<std::ops::Range<usize>> = Vec::new();
+    for index in changed {
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(rows.len());
+        match groups.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => groups.push(start..end),
+        }
+    }
+    groups
+}
+
+fn build_combined_hunk(
+    rows: &[CombinedRow],
+    range: std::ops::Range<usize>,
+    parent_count: usize,
+) -> CombinedHunk {
+    let hunk_rows = &rows[range.clone()];
+
+    let mut old_ranges = Vec::with_capacity(parent_count);
+    for p in 0..parent_count {
+        let len = hunk_rows
+            .iter()
+            .filter(|r| r.old_lines[p].is_some())
+            .count();
+        let before = rows[..range.start]
+            .iter()
+            .filter(|r| r.old_lines[p].is_some())
+            .count();
+        old_ranges.push((hunk_start(before, len), len));
+    }
+    let new_len = hunk_rows.iter().filter(|r| r.new_line.is_some()).count();
+    let new_before = rows[..range.start]
+        .iter()
+        .filter(|r| r.new_line.is_some())
+        .count();
+    let new_start = hunk_start(new_before, new_len);
+
+    let markers = "@".repeat(parent_count + 1);
+    let old_header: Vec<String> = old_ranges
+        .iter()
+        .map(|(start, len)| format!("-{}", format_range(*start, *len)))
+        .collect();
+
+    CombinedHunk {
+        header: format!(
+            "{markers} {} +{} {markers}",
+            old_header.join(" "),
+            format_range(new_start, new_len)
+        ),
+        old_starts: old_ranges.iter().map(|(start, _)| *start).collect(),
+        old_lines: old_ranges.iter().map(|(_, len)| *len).collect(),
+        new_start,
+        new_lines: new_len,
+        lines: hunk_rows
+            .iter()
+            .map(|row| CombinedDiffLine {
+                origins: row.origins.clone(),
+                old_lines: row.old_lines.clone(),
+                new_line: row.new_line,
+                content: row.content.clone(),
+            })
+            .collect(),
+    }
+}
+
+pub fn render_combined_diff(files: &[CombinedFileDiff]) -> String {
+    let mut output = String::new();
+    for file in files {
+        let path = &file.path;
+        output.push_str(&format!("diff --cc {path}\n"));
+        if file.is_binary {
+            output.push_str("Binary files differ\n");
+            continue;
+        }
+        output.push_str(&format!("--- a/{path}\n+++ b/{path}\n"));
+        for hunk in &file.hunks {
+            output.push_str(&hunk.header);
+            output.push('\n');
+            for line in &hunk.lines {
+                for origin in &line.origins {
+                    output.push(match origin {
+                        DiffLineKind::Context => ' ',
+                        DiffLineKind::Addition => '+',
+                        DiffLineKind::Deletion => '-',
+                    });
+                }
+                output.push_str(&line.content);
+                output.push('\n');
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// Builds a merge where `a.txt` has a conflict resolved by hand and
+    /// `b.txt` is taken cleanly from the side branch.
+    fn create_repo_with_merge() -> (TempDir, TempDir, String) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        git(work, &["init", "-b", "main"]);
+        git(work, &["config", "user.name", "Test"]);
+        git(work, &["config", "user.email", "test@test.com"]);
+
+        std::fs::write(work.join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        std::fs::write(work.join("b.txt"), "base\n").unwrap();
+        git(work, &["add", "."]);
+        git(work, &["commit", "-m", "base"]);
+
+        git(work, &["checkout", "-b", "side"]);
+        std::fs::write(work.join("a.txt"), "one\nside\nthree\n").unwrap();
+        std::fs::write(work.join("b.txt"), "side\n").unwrap();
+        git(work, &["commit", "-am", "side"]);
+
+        git(work, &["checkout", "main"]);
+        std::fs::write(work.join("a.txt"), "one\nmain\nthree\n").unwrap();
+        git(work, &["commit", "-am", "main"]);
+
+        git(work, &["merge", "side"]);
+        std::fs::write(work.join("a.txt"), "one\nresolved\nthree\n").unwrap();
+        git(work, &["add", "."]);
+        git(work, &["commit", "--no-edit"]);
+        let merge_oid = git(work, &["rev-parse", "HEAD"]);
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+
+        (work_dir, bare_dir, merge_oid)
+    }
+
+    #[test]
+    fn test_diff_commit_to_each_parent() {
+        let (_work, bare, merge_oid) = create_repo_with_merge();
+        let diffs =
+            diff_commit_to_each_parent(bare.path(), &merge_oid, &DiffOptions::default()).unwrap();
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].files.len(), 2);
+        assert_eq!(diffs[0].files[1].path, "b.txt");
+        assert_eq!(diffs[1].files.len(), 1);
+        assert_eq!(diffs[1].files[0].path, "a.txt");
+    }
+
+    #[test]
+    fn test_combined_diff_shows_conflict_resolution_only() {
+        let (_work, bare, merge_oid) = create_repo_with_merge();
+        let files = combined_diff(bare.path(), &merge_oid, &DiffOptions::default()).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "a.txt");
+
+        let text = render_combined_diff(&files);
+        assert_eq!(
+            text,
+            "diff --cc a.txt\n--- a/a.txt\n+++ b/a.txt\n@@@ -1,3 -1,3 +1,3 @@@\n  one\n- main\n -side\n++resolved\n  three\n"
+        );
+    }
+
+    #[test]
+    fn test_combined_hunks_drop_one_sided_changes() {
+        let hunks = build_combined_hunks(&["a\nb\n", "a\nB\n"], "a\nB\n", &DiffOptions::default());
+        assert!(hunks.is_empty());
+    }
+}
@@ -5,24 +5,11 @@ use gix::ObjectId;
 use petgraph::graph::{DiGraph, NodeIndex};
 
 use crate::error::AppError;
+use crate::git::actor::decode_signature;
+use crate::git::message::decode_message;
 use crate::git::refs::list_refs;
 use crate::git::repository::open_bare_repo;
-use crate::types::{CommitDag, DagEdge, DagNode, RefInfo};
-
-fn parse_author_info(raw: &str) -> (String, i64) {
-    match (raw.find('<'), raw.find('>')) {
-        (Some(lt), Some(gt)) if lt < gt => {
-            let name = raw[..lt].trim().to_string();
-            let timestamp = raw[gt + 1..]
-                .split_whitespace()
-                .next()
-                .and_then(|t| t.parse().ok())
-                .unwrap_or(0);
-            (name, timestamp)
-        }
-        _ => (raw.to_string(), 0),
-    }
-}
+use crate::types::{CommitDag, DagEdge, DagNode, RefInfo, RefType};
 
 pub fn build_commit_dag(
     repo_path: &Path,
@@ -32,8 +19,13 @@ pub fn build_commit_dag(
     let repo = open_bare_repo(repo_path)?;
     let refs = list_refs(repo_path)?;
 
+    // HEAD is already marked through `is_head`, and other symbolic refs only
+    // repeat the branch they point to.
     let mut ref_map: HashMap<String, Vec<RefInfo>> = HashMap::new();
-    for r in &refs {
+    for r in refs
+        .iter()
+        .filter(|r| !matches!(r.ref_type, RefType::Head | RefType::Symbolic))
+    {
         ref_map
             .entry(r.target_oid.clone())
             .or_default()
@@ -46,8 +38,10 @@ pub fn build_commit_dag(
                 .map_err(|e| AppError::Git(e.to_string()))?;
             vec![id]
         }
+        // Notes and stash commits are bookkeeping, not project history.
         None => refs
             .iter()
+            .filter(|r| !matches!(r.ref_type, RefType::Notes | RefType::Stash))
             .filter_map(|r| ObjectId::from_hex(r.target_oid.as_bytes()).ok())
             .collect(),
     };
@@ -89,15 +83,18 @@ pub fn build_commit_dag(
         let short_oid = info.id.to_hex_with_len(7).to_string();
         let node_refs = ref_map.remove(&oid_str).unwrap_or_default();
 
-        let author_raw = commit_ref.author.to_string();
-        let (author_name, timestamp) = parse_author_info(&author_raw);
+        let author = decode_signature(
+            commit_ref
+                .author()
+                .map_err(|e| AppError::Git(e.to_string()))?,
+        );
 
         let node = DagNode {
             oid: oid_str.clone(),
             short_oid,
-            message: commit_ref.message.to_string(),
-            author_name,
-            timestamp,
+            message: decode_message(commit_ref.message, commit_ref.encoding.map(|e| e.as_ref())),
+            author_name: author.name,
+            timestamp: author.timestamp,
             refs: node_refs,
             column: 0,
         };
@@ -1,17 +1,72 @@
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::path::Path;
 
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use gix::attrs::State;
+use gix::object::tree::EntryMode;
 use gix::ObjectId;
-use similar::TextDiff;
+use similar::{capture_diff_slices, group_diff_ops, Algorithm, ChangeTag, DiffOp};
 
 use crate::error::AppError;
+use crate::git::attributes::TreeAttributes;
+use crate::git::inline::annotate_inline_changes;
+use crate::git::pathspec::Pathspec;
+use crate::git::rename::detect_renames;
 use crate::git::repository::open_bare_repo;
+use crate::git::revision::resolve_commit;
+use crate::types::{
+    BinaryPreview, ChangedFile, DiffAlgorithm, DiffLine, DiffLineKind, DiffOptions, FileDiff,
+    FileStatus, Hunk,
+};
 
-pub fn diff_commit_to_parent(repo_path: &Path, oid_str: &str) -> Result<String, AppError> {
+const BINARY_CHECK_BYTES: usize = 8000;
+const MAX_PREVIEW_BYTES: usize = 2 * 1024 * 1024;
+
+#[cfg(test)]
+thread_local! {
+    /// Trees decoded and blobs read on this thread, so tests can check how
+    /// much a diff reads.
+    static TREES_DECODED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    static BLOBS_READ: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+pub struct TreeChange {
+    pub status: FileStatus,
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub old_id: Option<ObjectId>,
+    pub new_id: Option<ObjectId>,
+    pub similarity: Option<u8>,
+}
+
+impl TreeChange {
+    pub fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+pub fn diff_commit_to_parent(
+    repo_path: &Path,
+    oid_str: &str,
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, AppError> {
     let repo = open_bare_repo(repo_path)?;
-    let oid = ObjectId::from_hex(oid_str.as_bytes())
-        .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?;
+    let oid = resolve_commit(&repo, oid_str)?;
+
+    let (old_tree_id, new_tree_id) = commit_trees(&repo, oid)?;
+    diff_trees(&repo, old_tree_id, new_tree_id, options)
+}
 
+/// Returns the tree of the commit's first parent (if any) and its own tree.
+pub fn commit_trees(
+    repo: &gix::Repository,
+    oid: ObjectId,
+) -> Result<(Option<ObjectId>, ObjectId), AppError> {
     let commit = repo
         .find_object(oid)
         .map_err(|e| AppError::Git(e.to_string()))?
@@ -30,20 +85,37 @@ pub fn diff_commit_to_parent(repo_path: &Path, oid_str: &str) -> Result<String,
             Some(parent_ref.tree())
         });
 
-    diff_trees(&repo, old_tree_id, new_tree_id)
+    Ok((old_tree_id, new_tree_id))
 }
 
-pub fn generate_unified_diff_text(
+pub fn commit_tree_id(repo: &gix::Repository, oid: ObjectId) -> Result<ObjectId, AppError> {
+    repo.find_object(oid)
+        .map_err(|e| AppError::Git(e.to_string()))?
+        .into_commit()
+        .tree_id()
+        .map(|id| id.detach())
+        .map_err(|e| AppError::Git(e.to_string()))
+}
+
+pub fn diff_commit_to_parent_text(
+    repo_path: &Path,
+    oid_str: &str,
+    options: &DiffOptions,
+) -> Result<String, AppError> {
+    let files = diff_commit_to_parent(repo_path, oid_str, options)?;
+    Ok(render_unified_diff(&files))
+}
+
+pub fn generate_file_diffs(
     repo_path: &Path,
     old_oid_str: &str,
     new_oid_str: &str,
-) -> Result<String, AppError> {
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, AppError> {
     let repo = open_bare_repo(repo_path)?;
 
-    let old_oid = ObjectId::from_hex(old_oid_str.as_bytes())
-        .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?;
-    let new_oid = ObjectId::from_hex(new_oid_str.as_bytes())
-        .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?;
+    let old_oid = resolve_commit(&repo, old_oid_str)?;
+    let new_oid = resolve_commit(&repo, new_oid_str)?;
 
     let old_commit = repo
         .find_object(old_oid)
@@ -57,123 +129,992 @@ pub fn generate_unified_diff_text(
         .into_commit();
     let new_ref = new_commit.decode().map_err(|e| AppError::Git(e.to_string()))?;
 
-    diff_trees(&repo, Some(old_ref.tree()), new_ref.tree())
+    diff_trees(&repo, Some(old_ref.tree()), new_ref.tree(), options)
+}
+
+pub fn generate_unified_diff_text(
+    repo_path: &Path,
+    old_oid_str: &str,
+    new_oid_str: &str,
+    options: &DiffOptions,
+) -> Result<String, AppError> {
+    let files = generate_file_diffs(repo_path, old_oid_str, new_oid_str, options)?;
+    Ok(render_unified_diff(&files))
 }
 
-fn diff_trees(
+/// Lists what changed between two commits, or between `new_oid` and its first
+/// parent when `old_oid` is `None`, without reading or diffing any file content
+/// beyond what rename detection needs.
+pub fn list_changed_files(
+    repo_path: &Path,
+    old_oid_str: Option<&str>,
+    new_oid_str: &str,
+    options: &DiffOptions,
+) -> Result<Vec<ChangedFile>, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let (old_tree_id, new_tree_id) = resolve_trees(&repo, old_oid_str, new_oid_str)?;
+    let changes = changes_between(&repo, old_tree_id, new_tree_id, options)?;
+
+    Ok(changes
+        .into_iter()
+        .map(|change| ChangedFile {
+            path: change.path().to_string(),
+            old_path: change.old_path,
+            status: change.status,
+            similarity: change.similarity,
+        })
+        .collect())
+}
+
+/// Diffs only the files matching `pathspecs`, so the UI can load file diffs on
+/// demand. A renamed file matches if either its old or new path does.
+///
+/// Rename and copy detection only ever pairs an added file with a deleted or
+/// modified one, so the rest of the change set is kept for it only when a
+/// matching file was added or deleted; diffing a modified file reads no other
+/// blobs.
+pub fn diff_paths(
+    repo_path: &Path,
+    old_oid_str: Option<&str>,
+    new_oid_str: &str,
+    pathspecs: &[String],
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let (old_tree_id, new_tree_id) = resolve_trees(&repo, old_oid_str, new_oid_str)?;
+    let pathspec = Pathspec::new(pathspecs);
+
+    let matches = |change: &TreeChange| {
+        [&change.old_path, &change.new_path]
+            .into_iter()
+            .flatten()
+            .any(|path| pathspec.matches(path))
+    };
+    let is_pairable =
+        |change: &TreeChange| matches!(change.status, FileStatus::Added | FileStatus::Deleted);
+
+    let mut changes = collect_changes(&repo, old_tree_id, new_tree_id)?;
+    if options.detect_renames {
+        let pairing = changes.iter().any(|c| matches(c) && is_pairable(c));
+        changes.retain(|c| matches(c) || (pairing && (is_pairable(c) || options.detect_copies)));
+        changes = detect_renames(&repo, changes, options)?;
+    }
+    changes.retain(matches);
+    build_file_diffs(&repo, changes, new_tree_id, options)
+}
+
+fn resolve_trees(
+    repo: &gix::Repository,
+    old_oid_str: Option<&str>,
+    new_oid_str: &str,
+) -> Result<(Option<ObjectId>, ObjectId), AppError> {
+    let new_oid = resolve_commit(repo, new_oid_str)?;
+    let Some(old_oid_str) = old_oid_str else {
+        return commit_trees(repo, new_oid);
+    };
+    let old_oid = resolve_commit(repo, old_oid_str)?;
+
+    let (_, old_tree_id) = commit_trees(repo, old_oid)?;
+    let (_, new_tree_id) = commit_trees(repo, new_oid)?;
+    Ok((Some(old_tree_id), new_tree_id))
+}
+
+pub fn diff_trees(
     repo: &gix::Repository,
     old_tree_id: Option<ObjectId>,
     new_tree_id: ObjectId,
-) -> Result<String, AppError> {
-    let old_blobs = match old_tree_id {
-        Some(id) => collect_tree_blobs(repo, id, "")?,
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, AppError> {
+    let changes = changes_between(repo, old_tree_id, new_tree_id, options)?;
+    build_file_diffs(repo, changes, new_tree_id, options)
+}
+
+fn build_file_diffs(
+    repo: &gix::Repository,
+    changes: Vec<TreeChange>,
+    new_tree_id: ObjectId,
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, AppError> {
+    let mut attributes = TreeAttributes::new(repo, new_tree_id)?;
+    changes
+        .into_iter()
+        .map(|change| build_file_diff(repo, &mut attributes, change, options))
+        .collect()
+}
+
+pub fn changes_between(
+    repo: &gix::Repository,
+    old_tree_id: Option<ObjectId>,
+    new_tree_id: ObjectId,
+    options: &DiffOptions,
+) -> Result<Vec<TreeChange>, AppError> {
+    let changes = collect_changes(repo, old_tree_id, new_tree_id)?;
+    if options.detect_renames {
+        detect_renames(repo, changes, options)
+    } else {
+        Ok(changes)
+    }
+}
+
+fn build_file_diff(
+    repo: &gix::Repository,
+    attributes: &mut TreeAttributes,
+    change: TreeChange,
+    options: &DiffOptions,
+) -> Result<FileDiff, AppError> {
+    let path = change.path().to_string();
+    let old_data = change.old_id.map(|id| read_blob(repo, id)).transpose()?;
+    let new_data = change.new_id.map(|id| read_blob(repo, id)).transpose()?;
+
+    let old = old_data.as_deref().unwrap_or_default();
+    let new = new_data.as_deref().unwrap_or_default();
+    let is_binary = is_binary_change(attributes, &path, old, new)?;
+
+    let (hunks, preview) = if is_binary {
+        let preview = image_mime_type(&path)
+            .filter(|_| options.include_image_previews)
+            .map(|mime_type| BinaryPreview {
+                mime_type: mime_type.to_string(),
+                old_base64: old_data.as_deref().and_then(encode_preview),
+                new_base64: new_data.as_deref().and_then(encode_preview),
+            });
+        (Vec::new(), preview)
+    } else {
+        let hunks = build_hunks(
+            &String::from_utf8_lossy(old),
+            &String::from_utf8_lossy(new),
+            options,
+        );
+        (hunks, None)
+    };
+
+    Ok(FileDiff {
+        path,
+        old_path: change.old_path,
+        new_path: change.new_path,
+        status: change.status,
+        similarity: change.similarity,
+        is_binary,
+        old_size: old_data.as_ref().map(|d| d.len() as u64),
+        new_size: new_data.as_ref().map(|d| d.len() as u64),
+        preview,
+        hunks,
+    })
+}
+
+/// Compares two trees level by level, descending only into subtrees whose ids
+/// differ, so the cost scales with the size of the change rather than the repo.
+pub fn collect_changes(
+    repo: &gix::Repository,
+    old_tree_id: Option<ObjectId>,
+    new_tree_id: ObjectId,
+) -> Result<Vec<TreeChange>, AppError> {
+    let mut changes = Vec::new();
+    diff_tree_level(repo, old_tree_id, Some(new_tree_id), "", &mut changes)?;
+    changes.sort_by(|a, b| a.path().cmp(b.path()));
+    Ok(changes)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    Blob,
+    Tree,
+}
+
+fn diff_tree_level(
+    repo: &gix::Repository,
+    old_tree_id: Option<ObjectId>,
+    new_tree_id: Option<ObjectId>,
+    prefix: &str,
+    changes: &mut Vec<TreeChange>,
+) -> Result<(), AppError> {
+    let old_entries = match old_tree_id {
+        Some(id) => read_tree_entries(repo, id)?,
+        None => BTreeMap::new(),
+    };
+    let new_entries = match new_tree_id {
+        Some(id) => read_tree_entries(repo, id)?,
         None => BTreeMap::new(),
     };
 
-    let new_blobs = collect_tree_blobs(repo, new_tree_id, "")?;
+    let mut names: Vec<&String> = old_entries.keys().chain(new_entries.keys()).collect();
+    names.sort();
+    names.dedup();
 
-    let mut all_paths: Vec<String> = old_blobs
-        .keys()
-        .chain(new_blobs.keys())
-        .cloned()
-        .collect();
-    all_paths.sort();
-    all_paths.dedup();
+    for name in names {
+        let old = old_entries.get(name).copied();
+        let new = new_entries.get(name).copied();
+        if old == new {
+            continue;
+        }
 
-    let mut output = String::new();
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}/{name}")
+        };
 
-    for path in &all_paths {
-        match (old_blobs.get(path), new_blobs.get(path)) {
-            (None, Some(new_id)) => {
-                let content = read_blob_text(repo, *new_id)?;
-                write_addition_diff(&mut output, path, &content);
-            }
-            (Some(old_id), None) => {
-                let content = read_blob_text(repo, *old_id)?;
-                write_deletion_diff(&mut output, path, &content);
-            }
-            (Some(old_id), Some(new_id)) if old_id != new_id => {
-                let old_content = read_blob_text(repo, *old_id)?;
-                let new_content = read_blob_text(repo, *new_id)?;
-                write_modification_diff(&mut output, path, &old_content, &new_content);
-            }
-            _ => {}
+        let subtree = |entry: Option<(EntryKind, ObjectId)>| {
+            entry.and_then(|(kind, id)| (kind == EntryKind::Tree).then_some(id))
+        };
+        let blob = |entry: Option<(EntryKind, ObjectId)>| {
+            entry.and_then(|(kind, id)| (kind == EntryKind::Blob).then_some(id))
+        };
+
+        let (old_subtree, new_subtree) = (subtree(old), subtree(new));
+        if old_subtree.is_some() || new_subtree.is_some() {
+            diff_tree_level(repo, old_subtree, new_subtree, &path, changes)?;
         }
+
+        let (old_id, new_id) = (blob(old), blob(new));
+        let status = match (old_id, new_id) {
+            (None, None) => continue,
+            (None, Some(_)) => FileStatus::Added,
+            (Some(_), None) => FileStatus::Deleted,
+            (Some(_), Some(_)) => FileStatus::Modified,
+        };
+        changes.push(TreeChange {
+            status,
+            old_path: old_id.map(|_| path.clone()),
+            new_path: new_id.map(|_| path.clone()),
+            old_id,
+            new_id,
+            similarity: None,
+        });
     }
 
-    Ok(output)
+    Ok(())
 }
 
-fn collect_tree_blobs(
+/// Decodes a tree object into `(name, mode, id)` entries, in tree order.
+pub fn decode_tree(
     repo: &gix::Repository,
     tree_id: ObjectId,
-    prefix: &str,
-) -> Result<BTreeMap<String, ObjectId>, AppError> {
+) -> Result<Vec<(String, EntryMode, ObjectId)>, AppError> {
     let tree_obj = repo
         .find_object(tree_id)
         .map_err(|e| AppError::Git(e.to_string()))?
         .into_tree();
     let tree_ref = tree_obj.decode().map_err(|e| AppError::Git(e.to_string()))?;
+    #[cfg(test)]
+    TREES_DECODED.with(|count| count.set(count.get() + 1));
 
-    let mut blobs = BTreeMap::new();
+    Ok(tree_ref
+        .entries
+        .iter()
+        .map(|entry| (entry.filename.to_string(), entry.mode, entry.oid.to_owned()))
+        .collect())
+}
 
-    for entry in &tree_ref.entries {
-        let name = entry.filename.to_string();
-        let path = if prefix.is_empty() {
-            name
+/// Finds the entry at the slash-separated `path` below the tree `tree_id`,
+/// like `git rev-parse <tree>:<path>`.
+pub fn lookup_path(
+    repo: &gix::Repository,
+    tree_id: ObjectId,
+    path: &str,
+) -> Result<Option<(EntryMode, ObjectId)>, AppError> {
+    let entry = repo
+        .find_object(tree_id)
+        .map_err(|e| AppError::Git(e.to_string()))?
+        .into_tree()
+        .lookup_entry(path.split('/').map(str::as_bytes))
+        .map_err(|e| AppError::Git(e.to_string()))?;
+    Ok(entry.map(|e| (e.mode(), e.object_id())))
+}
+
+/// The blob at `path` in the commit `commit_oid`, if there is one.
+pub fn blob_at(
+    repo: &gix::Repository,
+    commit_oid: ObjectId,
+    path: &str,
+) -> Result<Option<ObjectId>, AppError> {
+    let entry = lookup_path(repo, commit_tree_id(repo, commit_oid)?, path)?;
+    Ok(entry.filter(|(mode, _)| mode.is_blob()).map(|(_, id)| id))
+}
+
+fn read_tree_entries(
+    repo: &gix::Repository,
+    tree_id: ObjectId,
+) -> Result<BTreeMap<String, (EntryKind, ObjectId)>, AppError> {
+    let mut entries = BTreeMap::new();
+    for (name, mode, oid) in decode_tree(repo, tree_id)? {
+        let kind = if mode.is_blob() {
+            EntryKind::Blob
+        } else if mode.is_tree() {
+            EntryKind::Tree
         } else {
-            format!("{prefix}/{name}")
+            continue;
         };
-
-        if entry.mode.is_blob() {
-            blobs.insert(path, entry.oid.to_owned());
-        } else if entry.mode.is_tree() {
-            let sub_blobs = collect_tree_blobs(repo, entry.oid.to_owned(), &path)?;
-            blobs.extend(sub_blobs);
-        }
+        entries.insert(name, (kind, oid));
     }
 
-    Ok(blobs)
+    Ok(entries)
 }
 
-fn read_blob_text(repo: &gix::Repository, oid: ObjectId) -> Result<String, AppError> {
+pub fn read_blob(repo: &gix::Repository, oid: ObjectId) -> Result<Vec<u8>, AppError> {
     let obj = repo
         .find_object(oid)
         .map_err(|e| AppError::Git(e.to_string()))?;
-    Ok(String::from_utf8_lossy(&obj.data).to_string())
-}
-
-fn write_addition_diff(output: &mut String, path: &str, content: &str) {
-    output.push_str(&format!(
-        "diff --git a/{path} b/{path}\nnew file mode 100644\n--- /dev/null\n+++ b/{path}\n"
-    ));
-    let lines: Vec<&str> = content.lines().collect();
-    let count = lines.len();
-    output.push_str(&format!("@@ -0,0 +1,{count} @@\n"));
-    for line in &lines {
-        output.push_str(&format!("+{line}\n"));
-    }
-}
-
-fn write_deletion_diff(output: &mut String, path: &str, content: &str) {
-    output.push_str(&format!(
-        "diff --git a/{path} b/{path}\ndeleted file mode 100644\n--- a/{path}\n+++ /dev/null\n"
-    ));
-    let lines: Vec<&str> = content.lines().collect();
-    let count = lines.len();
-    output.push_str(&format!("@@ -1,{count} +0,0 @@\n"));
-    for line in &lines {
-        output.push_str(&format!("-{line}\n"));
-    }
-}
-
-fn write_modification_diff(output: &mut String, path: &str, old_content: &str, new_content: &str) {
-    output.push_str(&format!("diff --git a/{path} b/{path}\n"));
-    let diff = TextDiff::from_lines(old_content, new_content);
-    output.push_str(&format!(
-        "{}",
-        diff.unified_diff()
-            .header(&format!("a/{path}"), &format!("b/{path}"))
-            .context_radius(3)
-    ));
+    #[cfg(test)]
+    BLOBS_READ.with(|count| count.set(count.get() + 1));
+    Ok(obj.detach().data)
+}
+
+pub fn read_blob_text(repo: &gix::Repository, oid: ObjectId) -> Result<String, AppError> {
+    let data = read_blob(repo, oid)?;
+    Ok(String::from_utf8_lossy(&data).to_string())
+}
+
+/// The `diff` attribute wins over content sniffing, so `-diff` (or `binary`)
+/// forces a binary diff and `diff` forces a textual one.
+pub fn is_binary_change(
+    attributes: &mut TreeAttributes,
+    path: &str,
+    old: &[u8],
+    new: &[u8],
+) -> Result<bool, AppError> {
+    Ok(match attributes.state(path, "diff")? {
+        State::Unset => true,
+        State::Set => false,
+        _ => is_binary_content(old) || is_binary_content(new),
+    })
+}
+
+/// Same heuristic as git: a NUL byte within the first 8000 bytes marks the
+/// content as binary.
+pub fn is_binary_content(data: &[u8]) -> bool {
+    data.iter().take(BINARY_CHECK_BYTES).any(|&b| b == 0)
+}
+
+fn image_mime_type(path: &str) -> Option<&'static str> {
+    let extension = path.rsplit_once('.')?.1.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "bmp" => Some("image/bmp"),
+        "ico" => Some("image/x-icon"),
+        _ => None,
+    }
+}
+
+fn encode_preview(data: &[u8]) -> Option<String> {
+    (data.len() <= MAX_PREVIEW_BYTES).then(|| STANDARD.encode(data))
+}
+
+fn build_hunks(old_content: &str, new_content: &str, options: &DiffOptions) -> Vec<Hunk> {
+    let old_lines: Vec<&str> = old_content.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new_content.split_inclusive('\n').collect();
+
+    let ops = diff_lines(&old_lines, &new_lines, options);
+    group_diff_ops(ops, options.context_lines)
+        .iter()
+        .filter(|group| {
+            !options.ignore_blank_lines
+                || group
+                    .iter()
+                    .any(|op| !is_blank_change(op, &old_lines, &new_lines))
+        })
+        .map(|group| build_hunk(group, &old_lines, &new_lines, options))
+        .collect()
+}
+
+/// Diffs two line slices with the algorithm and whitespace handling from `options`.
+/// Lines are compared by a normalized key, so the returned ranges still index the
+/// original lines.
+pub fn diff_lines(old_lines: &[&str], new_lines: &[&str], options: &DiffOptions) -> Vec<DiffOp> {
+    let algorithm = match options.algorithm {
+        DiffAlgorithm::Myers => Algorithm::Myers,
+        DiffAlgorithm::Patience => Algorithm::Patience,
+        DiffAlgorithm::Lcs => Algorithm::Lcs,
+    };
+    let old_keys: Vec<Cow<str>> = old_lines.iter().map(|l| line_key(l, options)).collect();
+    let new_keys: Vec<Cow<str>> = new_lines.iter().map(|l| line_key(l, options)).collect();
+    capture_diff_slices(algorithm, &old_keys, &new_keys)
+}
+
+fn line_key<'a>(line: &'a str, options: &DiffOptions) -> Cow<'a, str> {
+    if options.ignore_whitespace {
+        Cow::Owned(line.chars().filter(|c| !c.is_whitespace()).collect())
+    } else if options.ignore_eol {
+        Cow::Borrowed(line.trim_end())
+    } else {
+        Cow::Borrowed(line)
+    }
+}
+
+/// True when a change only adds or removes blank lines, which
+/// `ignore_blank_lines` hides unless it sits next to a real change.
+pub fn is_blank_change(op: &DiffOp, old_lines: &[&str], new_lines: &[&str]) -> bool {
+    if let DiffOp::Equal { .. } = op {
+        return true;
+    }
+    old_lines[op.old_range()]
+        .iter()
+        .chain(&new_lines[op.new_range()])
+        .all(|line| line.trim().is_empty())
+}
+
+fn build_hunk(
+    group: &[DiffOp],
+    old_lines: &[&str],
+    new_lines: &[&str],
+    options: &DiffOptions,
+) -> Hunk {
+    let first = &group[0];
+    let last = &group[group.len() - 1];
+    let old_range = first.old_range().start..last.old_range().end;
+    let new_range = first.new_range().start..last.new_range().end;
+    let old_start = hunk_start(old_range.start, old_range.len());
+    let new_start = hunk_start(new_range.start, new_range.len());
+
+    let mut lines = Vec::new();
+    for op in group {
+        for change in op.iter_changes(old_lines, new_lines) {
+            let kind = match change.tag() {
+                ChangeTag::Equal => DiffLineKind::Context,
+                ChangeTag::Insert => DiffLineKind::Addition,
+                ChangeTag::Delete => DiffLineKind::Deletion,
+            };
+            let raw = change.value();
+            lines.push(DiffLine {
+                kind,
+                old_line: change.old_index().map(|i| i + 1),
+                new_line: change.new_index().map(|i| i + 1),
+                content: raw.strip_suffix('\n').unwrap_or(raw).to_string(),
+                missing_newline: !raw.ends_with('\n'),
+                spans: Vec::new(),
+            });
+        }
+    }
+    annotate_inline_changes(&mut lines, options.inline_granularity);
+
+    Hunk {
+        header: format!(
+            "@@ -{} +{} @@",
+            format_range(old_start, old_range.len()),
+            format_range(new_start, new_range.len())
+        ),
+        old_start,
+        old_lines: old_range.len(),
+        new_start,
+        new_lines: new_range.len(),
+        lines,
+    }
+}
+
+pub fn hunk_start(index: usize, len: usize) -> usize {
+    if len == 0 {
+        index
+    } else {
+        index + 1
+    }
+}
+
+pub fn format_range(start: usize, len: usize) -> String {
+    if len == 1 {
+        start.to_string()
+    } else {
+        format!("{start},{len}")
+    }
+}
+
+pub fn render_unified_diff(files: &[FileDiff]) -> String {
+    let mut output = String::new();
+    for file in files {
+        write_file_header(&mut output, file);
+        for hunk in &file.hunks {
+            write_hunk(&mut output, hunk);
+        }
+    }
+    output
+}
+
+fn write_file_header(output: &mut String, file: &FileDiff) {
+    let old_path = file.old_path.as_deref().unwrap_or(&file.path);
+    let new_path = file.new_path.as_deref().unwrap_or(&file.path);
+    output.push_str(&format!("diff --git a/{old_path} b/{new_path}\n"));
+
+    let (old_label, new_label) = match file.status {
+        FileStatus::Added => {
+            output.push_str("new file mode 100644\n");
+            ("/dev/null".to_string(), format!("b/{new_path}"))
+        }
+        FileStatus::Deleted => {
+            output.push_str("deleted file mode 100644\n");
+            (format!("a/{old_path}"), "/dev/null".to_string())
+        }
+        FileStatus::Renamed | FileStatus::Copied => {
+            let verb = if file.status == FileStatus::Renamed {
+                "rename"
+            } else {
+                "copy"
+            };
+            output.push_str(&format!(
+                "similarity index {}%\n{verb} from {old_path}\n{verb} to {new_path}\n",
+                file.similarity.unwrap_or(100)
+            ));
+            (format!("a/{old_path}"), format!("b/{new_path}"))
+        }
+        FileStatus::Modified => (format!("a/{old_path}"), format!("b/{new_path}")),
+    };
+
+    if file.is_binary {
+        output.push_str(&format!("Binary files {old_label} and {new_label} differ\n"));
+    } else if !file.hunks.is_empty() {
+        output.push_str(&format!("--- {old_label}\n+++ {new_label}\n"));
+    }
+}
+
+fn write_hunk(output: &mut String, hunk: &Hunk) {
+    output.push_str(&hunk.header);
+    output.push('\n');
+    for line in &hunk.lines {
+        let prefix = match line.kind {
+            DiffLineKind::Context => ' ',
+            DiffLineKind::Addition => '+',
+            DiffLineKind::Deletion => '-',
+        };
+        output.push(prefix);
+        output.push_str(&line.content);
+        output.push('\n');
+        if line.missing_newline {
+            output.push_str("\\ No newline at end of file\n");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn init_work_repo() -> TempDir {
+        let work_dir = TempDir::new().unwrap();
+        git(work_dir.path(), &["init"]);
+        git(work_dir.path(), &["config", "user.name", "Test"]);
+        git(work_dir.path(), &["config", "user.email", "test@test.com"]);
+        work_dir
+    }
+
+    fn commit_all(work: &Path, message: &str) -> String {
+        git(work, &["add", "-A"]);
+        git(work, &["commit", "-m", message]);
+        git(work, &["rev-parse", "HEAD"])
+    }
+
+    fn clone_bare(work: &Path) -> TempDir {
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        bare_dir
+    }
+
+    /// Writes `dirs * files_per_dir` files plus a second commit touching a single
+    /// file straight into a bare repository via fast-import.
+    fn create_large_repo(dirs: usize, files_per_dir: usize) -> (TempDir, String, String) {
+        let bare_dir = TempDir::new().unwrap();
+        git(bare_dir.path(), &["init", "--bare"]);
+
+        let mut stream = String::from("blob\nmark :1\ndata 5\nsame\n\n");
+        stream.push_str("blob\nmark :2\ndata 8\nchanged\n\n");
+        stream.push_str("commit refs/heads/main\nmark :10\n");
+        stream.push_str("committer Test <test@test.com> 1700000000 +0000\ndata 5\nbase\n");
+        for d in 0..dirs {
+            for f in 0..files_per_dir {
+                stream.push_str(&format!("M 100644 :1 dir{d}/sub{}/file{f}.txt\n", f % 4));
+            }
+        }
+        stream.push_str("\ncommit refs/heads/main\nmark :11\n");
+        stream.push_str("committer Test <test@test.com> 1700000001 +0000\n");
+        stream.push_str("data 7\nchange\nfrom :10\n");
+        stream.push_str("M 100644 :2 dir7/sub1/file1.txt\n\n");
+
+        let mut child = Command::new("git")
+            .arg("-C")
+            .arg(bare_dir.path())
+            .args(["fast-import", "--quiet"])
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stream.as_bytes())
+            .unwrap();
+        assert!(child.wait().unwrap().success());
+
+        let base = git(bare_dir.path(), &["rev-parse", "main~1"]);
+        let head = git(bare_dir.path(), &["rev-parse", "main"]);
+        (bare_dir, base, head)
+    }
+
+    fn count_tree_entries(repo: &gix::Repository, tree_id: ObjectId) -> usize {
+        read_tree_entries(repo, tree_id)
+            .unwrap()
+            .values()
+            .map(|(kind, id)| match kind {
+                EntryKind::Tree => count_tree_entries(repo, *id),
+                EntryKind::Blob => 1,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_collect_changes_skips_unchanged_subtrees() {
+        let (bare, _base, head) = create_large_repo(200, 100);
+        let repo = open_bare_repo(bare.path()).unwrap();
+        let head = ObjectId::from_hex(head.as_bytes()).unwrap();
+        let (old_tree, new_tree) = commit_trees(&repo, head).unwrap();
+
+        assert_eq!(count_tree_entries(&repo, new_tree), 20_000);
+
+        TREES_DECODED.with(|count| count.set(0));
+        let changes = collect_changes(&repo, old_tree, new_tree).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path(), "dir7/sub1/file1.txt");
+        assert_eq!(changes[0].status, FileStatus::Modified);
+        // Only the old and new tree of each level on the changed path are read.
+        assert_eq!(TREES_DECODED.with(|count| count.get()), 2 * 3);
+    }
+
+    #[test]
+    fn test_collect_changes_type_changes() {
+        let work = init_work_repo();
+        std::fs::write(work.path().join("entry"), "file\n").unwrap();
+        std::fs::create_dir(work.path().join("dir")).unwrap();
+        std::fs::write(work.path().join("dir/a.txt"), "a\n").unwrap();
+        commit_all(work.path(), "first");
+
+        std::fs::remove_file(work.path().join("entry")).unwrap();
+        std::fs::create_dir(work.path().join("entry")).unwrap();
+        std::fs::write(work.path().join("entry/inner.txt"), "inner\n").unwrap();
+        std::fs::remove_dir_all(work.path().join("dir")).unwrap();
+        std::fs::write(work.path().join("dir"), "now a file\n").unwrap();
+        let head = commit_all(work.path(), "swap");
+        let bare = clone_bare(work.path());
+
+        let options = DiffOptions {
+            detect_renames: false,
+            ..DiffOptions::default()
+        };
+        let files = diff_commit_to_parent(bare.path(), &head, &options).unwrap();
+        let summary: Vec<(&str, FileStatus)> = files
+            .iter()
+            .map(|f| (f.path.as_str(), f.status.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("dir", FileStatus::Added),
+                ("dir/a.txt", FileStatus::Deleted),
+                ("entry", FileStatus::Deleted),
+                ("entry/inner.txt", FileStatus::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_commit_to_parent_structured() {
+        let work = init_work_repo();
+        std::fs::write(work.path().join("file.txt"), "one\ntwo\nthree\n").unwrap();
+        commit_all(work.path(), "first");
+        std::fs::write(work.path().join("file.txt"), "one\nTWO\nthree\n").unwrap();
+        std::fs::write(work.path().join("new.txt"), "fresh\n").unwrap();
+        let head = commit_all(work.path(), "second");
+        let bare = clone_bare(work.path());
+
+        let files = diff_commit_to_parent(bare.path(), &head, &DiffOptions::default()).unwrap();
+        assert_eq!(files.len(), 2);
+
+        let modified = &files[0];
+        assert_eq!(modified.path, "file.txt");
+        assert_eq!(modified.status, FileStatus::Modified);
+        assert_eq!(modified.hunks.len(), 1);
+        let hunk = &modified.hunks[0];
+        assert_eq!(hunk.header, "@@ -1,3 +1,3 @@");
+        let deleted: Vec<_> = hunk
+            .lines
+            .iter()
+            .filter(|l| l.kind == DiffLineKind::Deletion)
+            .collect();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].content, "two");
+        assert_eq!(deleted[0].old_line, Some(2));
+        assert_eq!(deleted[0].new_line, None);
+
+        let added = &files[1];
+        assert_eq!(added.path, "new.txt");
+        assert_eq!(added.status, FileStatus::Added);
+        assert_eq!(added.old_path, None);
+        assert_eq!(added.hunks[0].header, "@@ -0,0 +1 @@");
+    }
+
+    #[test]
+    fn test_diff_detects_renames() {
+        let work = init_work_repo();
+        let body: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+        std::fs::write(work.path().join("old_name.txt"), &body).unwrap();
+        std::fs::write(work.path().join("moved.txt"), "unchanged\n").unwrap();
+        commit_all(work.path(), "first");
+
+        std::fs::remove_file(work.path().join("old_name.txt")).unwrap();
+        let renamed_body = body.replace("line 20", "line twenty");
+        std::fs::write(work.path().join("new_name.txt"), renamed_body).unwrap();
+        std::fs::create_dir(work.path().join("sub")).unwrap();
+        std::fs::rename(work.path().join("moved.txt"), work.path().join("sub/moved.txt"))
+            .unwrap();
+        let head = commit_all(work.path(), "rename");
+        let bare = clone_bare(work.path());
+
+        let files = diff_commit_to_parent(bare.path(), &head, &DiffOptions::default()).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].status, FileStatus::Renamed);
+        assert_eq!(files[0].old_path.as_deref(), Some("old_name.txt"));
+        assert_eq!(files[0].new_path.as_deref(), Some("new_name.txt"));
+        assert_eq!(files[0].similarity, Some(95));
+        assert_eq!(files[1].status, FileStatus::Renamed);
+        assert_eq!(files[1].similarity, Some(100));
+        assert!(files[1].hunks.is_empty());
+
+        let strict = DiffOptions {
+            rename_threshold: 100,
+            ..DiffOptions::default()
+        };
+        let files = diff_commit_to_parent(bare.path(), &head, &strict).unwrap();
+        assert_eq!(files.len(), 3);
+    }
+
+    #[test]
+    fn test_changed_files_and_path_diff() {
+        let work = init_work_repo();
+        std::fs::create_dir_all(work.path().join("src/git")).unwrap();
+        std::fs::write(work.path().join("src/git/diff.rs"), "a\n").unwrap();
+        std::fs::write(work.path().join("README.md"), "readme\n").unwrap();
+        std::fs::write(work.path().join("notes.txt"), "notes\n").unwrap();
+        let first = commit_all(work.path(), "first");
+
+        std::fs::write(work.path().join("src/git/diff.rs"), "b\n").unwrap();
+        std::fs::write(work.path().join("README.md"), "readme!\n").unwrap();
+        std::fs::write(work.path().join("notes.txt"), "more notes\n").unwrap();
+        let head = commit_all(work.path(), "second");
+        let bare = clone_bare(work.path());
+        let options = DiffOptions::default();
+
+        let files = list_changed_files(bare.path(), None, &head, &options).unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["README.md", "notes.txt", "src/git/diff.rs"]);
+        assert!(files.iter().all(|f| f.status == FileStatus::Modified));
+
+        let range = list_changed_files(bare.path(), Some(&first), &head, &options).unwrap();
+        assert_eq!(range.len(), 3);
+
+        let pathspecs = vec!["src".to_string(), "*.md".to_string()];
+        let diffs = diff_paths(bare.path(), None, &head, &pathspecs, &options).unwrap();
+        let paths: Vec<&str> = diffs.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["README.md", "src/git/diff.rs"]);
+        assert_eq!(diffs[1].hunks.len(), 1);
+    }
+
+    #[test]
+    fn test_path_diff_reads_only_what_it_can_pair() {
+        let work = init_work_repo();
+        let body: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+        std::fs::write(work.path().join("old.txt"), &body).unwrap();
+        std::fs::write(work.path().join("gone.txt"), "unrelated\n").unwrap();
+        std::fs::write(work.path().join("notes.txt"), "notes\n").unwrap();
+        commit_all(work.path(), "first");
+
+        std::fs::remove_file(work.path().join("old.txt")).unwrap();
+        std::fs::remove_file(work.path().join("gone.txt")).unwrap();
+        std::fs::write(work.path().join("new.txt"), format!("{body}line 21\n")).unwrap();
+        std::fs::write(work.path().join("notes.txt"), "more notes\n").unwrap();
+        let head = commit_all(work.path(), "rename");
+        let bare = clone_bare(work.path());
+        let options = DiffOptions::default();
+
+        BLOBS_READ.with(|count| count.set(0));
+        let pathspecs = vec!["notes.txt".to_string()];
+        let notes = diff_paths(bare.path(), None, &head, &pathspecs, &options).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].status, FileStatus::Modified);
+        assert_eq!(BLOBS_READ.with(|count| count.get()), 2);
+
+        let pathspecs = vec!["new.txt".to_string()];
+        let renamed = diff_paths(bare.path(), None, &head, &pathspecs, &options).unwrap();
+        assert_eq!(renamed.len(), 1);
+        assert_eq!(renamed[0].status, FileStatus::Renamed);
+        assert_eq!(renamed[0].old_path.as_deref(), Some("old.txt"));
+    }
+
+    #[test]
+    fn test_diff_detects_copies() {
+        let work = init_work_repo();
+        let body: String = (1..=10).map(|i| format!("line {i}\n")).collect();
+        std::fs::write(work.path().join("source.txt"), &body).unwrap();
+        commit_all(work.path(), "first");
+
+        std::fs::write(work.path().join("source.txt"), format!("{body}line 11\n")).unwrap();
+        std::fs::write(work.path().join("copy.txt"), &body).unwrap();
+        let head = commit_all(work.path(), "copy");
+        let bare = clone_bare(work.path());
+
+        let options = DiffOptions {
+            detect_copies: true,
+            ..DiffOptions::default()
+        };
+        let files = diff_commit_to_parent(bare.path(), &head, &options).unwrap();
+        let copy = files.iter().find(|f| f.path == "copy.txt").unwrap();
+        assert_eq!(copy.status, FileStatus::Copied);
+        assert_eq!(copy.old_path.as_deref(), Some("source.txt"));
+        assert_eq!(copy.similarity, Some(100));
+
+        let text = render_unified_diff(&files);
+        assert!(text.contains("copy from source.txt\ncopy to copy.txt\n"));
+    }
+
+    #[test]
+    fn test_diff_binary_files() {
+        let work = init_work_repo();
+        std::fs::write(work.path().join("image.png"), b"\x89PNG\0\x01").unwrap();
+        std::fs::write(work.path().join("data.dat"), "looks like text\n").unwrap();
+        std::fs::write(work.path().join(".gitattributes"), "*.dat -diff\n").unwrap();
+        let head = commit_all(work.path(), "binary");
+        let bare = clone_bare(work.path());
+
+        let options = DiffOptions {
+            include_image_previews: true,
+            ..DiffOptions::default()
+        };
+        let files = diff_commit_to_parent(bare.path(), &head, &options).unwrap();
+
+        let data = files.iter().find(|f| f.path == "data.dat").unwrap();
+        assert!(data.is_binary);
+        assert!(data.hunks.is_empty());
+        assert!(data.preview.is_none());
+
+        let image = files.iter().find(|f| f.path == "image.png").unwrap();
+        assert!(image.is_binary);
+        assert_eq!(image.old_size, None);
+        assert_eq!(image.new_size, Some(6));
+        let preview = image.preview.as_ref().unwrap();
+        assert_eq!(preview.mime_type, "image/png");
+        assert_eq!(preview.new_base64.as_deref(), Some("iVBORwAB"));
+
+        let text = render_unified_diff(&files);
+        assert!(text.contains("Binary files /dev/null and b/image.png differ\n"));
+        assert!(!text.contains("+++ b/image.png"));
+    }
+
+    #[test]
+    fn test_render_unified_diff() {
+        let files = vec![FileDiff {
+            path: "a.txt".to_string(),
+            old_path: Some("a.txt".to_string()),
+            new_path: Some("a.txt".to_string()),
+            status: FileStatus::Modified,
+            similarity: None,
+            is_binary: false,
+            old_size: Some(3),
+            new_size: Some(4),
+            preview: None,
+            hunks: build_hunks("x\ny", "x\nz\n", &DiffOptions::default()),
+        }];
+
+        let text = render_unified_diff(&files);
+        assert_eq!(
+            text,
+            "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n x\n-y\n\\ No newline at end of file\n+z\n"
+        );
+    }
+
+    #[test]
+    fn test_build_hunks_identical_content() {
+        assert!(build_hunks("same\n", "same\n", &DiffOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_build_hunks_context_lines() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n";
+        let new = "1\n2\n3\nfour\n5\n6\n7\n";
+        let options = DiffOptions {
+            context_lines: 1,
+            ..DiffOptions::default()
+        };
+        let hunks = build_hunks(old, new, &options);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].header, "@@ -3,3 +3,3 @@");
+        assert_eq!(hunks[0].lines.len(), 4);
+    }
+
+    #[test]
+    fn test_build_hunks_ignore_whitespace() {
+        let old = "fn main() {\n    call(a, b);\n}\n";
+        let new = "fn main() {\n\tcall(a,b);  \n}\n";
+
+        let options = DiffOptions {
+            ignore_whitespace: true,
+            ..DiffOptions::default()
+        };
+        assert!(build_hunks(old, new, &options).is_empty());
+
+        let options = DiffOptions {
+            ignore_eol: true,
+            ..DiffOptions::default()
+        };
+        assert_eq!(build_hunks(old, new, &options).len(), 1);
+        assert!(build_hunks("a\r\n", "a \n", &options).is_empty());
+    }
+
+    #[test]
+    fn test_build_hunks_ignore_blank_lines() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\n";
+        let new = "a\n\nb\nc\nd\ne\nf\ng\nh\nI\n";
+        let options = DiffOptions {
+            context_lines: 1,
+            ignore_blank_lines: true,
+            ..DiffOptions::default()
+        };
+        let hunks = build_hunks(old, new, &options);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].old_start, 8);
+    }
+
+    #[test]
+    fn test_build_hunks_algorithms() {
+        let old = "a\nb\nc\nd\n";
+        let new = "a\nc\nb\nd\n";
+        for algorithm in [DiffAlgorithm::Myers, DiffAlgorithm::Patience, DiffAlgorithm::Lcs] {
+            let options = DiffOptions {
+                algorithm,
+                ..DiffOptions::default()
+            };
+            let hunks = build_hunks(old, new, &options);
+            let added = hunks[0]
+                .lines
+                .iter()
+                .filter(|l| l.kind == DiffLineKind::Addition)
+                .count();
+            assert_eq!(added, 1);
+        }
+    }
 }
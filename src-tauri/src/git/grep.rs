@@ -0,0 +1,304 @@
+use std::path::Path;
+
+use gix::ObjectId;
+
+use crate::error::AppError;
+use crate::git::diff::{
+    changes_between, collect_changes, commit_trees, is_binary_content, read_blob,
+};
+use crate::git::log::{parse_commit_from_repo, TextMatcher};
+use crate::git::pathspec::Pathspec;
+use crate::git::repository::open_bare_repo;
+use crate::git::revision::resolve_commit;
+use crate::types::{
+    DiffOptions, GrepMatch, GrepOptions, GrepResult, PickaxeFile, PickaxeHit, PickaxeOptions,
+};
+
+/// Like `git grep <pattern> <rev>`: searches every text blob in the tree of
+/// `rev` (HEAD by default), skipping binary files.
+pub fn grep_tree(
+    repo_path: &Path,
+    rev: Option<&str>,
+    pattern: &str,
+    options: &GrepOptions,
+) -> Result<GrepResult, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let oid = resolve_commit(&repo, rev.unwrap_or("HEAD"))?;
+    let matcher = TextMatcher::new(pattern, options.use_regex, options.case_insensitive)?;
+    let pathspec = Pathspec::new(&options.pathspecs);
+
+    let (_, tree_id) = commit_trees(&repo, oid)?;
+    let mut matches = Vec::new();
+    let mut truncated = false;
+    // Diffing against nothing lists every blob, sorted by path.
+    for entry in collect_changes(&repo, None, tree_id)? {
+        let (Some(path), Some(blob)) = (entry.new_path, entry.new_id) else {
+            continue;
+        };
+        if !pathspec.matches(&path) {
+            continue;
+        }
+        let data = read_blob(&repo, blob)?;
+        if is_binary_content(&data) {
+            continue;
+        }
+        let text = String::from_utf8_lossy(&data);
+        let remaining = options.max_matches.saturating_sub(matches.len());
+        let found = grep_text(&path, &text, &matcher, options.context_lines, remaining + 1);
+        if found.len() > remaining {
+            matches.extend(found.into_iter().take(remaining));
+            truncated = true;
+            break;
+        }
+        matches.extend(found);
+    }
+
+    Ok(GrepResult {
+        commit_oid: oid.to_string(),
+        matches,
+        truncated,
+    })
+}
+
+fn grep_text(
+    path: &str,
+    text: &str,
+    matcher: &TextMatcher,
+    context: usize,
+    limit: usize,
+) -> Vec<GrepMatch> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut matches = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if matches.len() >= limit {
+            break;
+        }
+        let match_ranges: Vec<(usize, usize)> = matcher.find_ranges(line).collect();
+        if match_ranges.is_empty() {
+            continue;
+        }
+        let owned = |range: &[&str]| range.iter().map(|l| l.to_string()).collect();
+        matches.push(GrepMatch {
+            path: path.to_string(),
+            line_number: index + 1,
+            line: line.to_string(),
+            match_ranges,
+            context_before: owned(&lines[index.saturating_sub(context)..index]),
+            context_after: owned(&lines[index + 1..(index + 1 + context).min(lines.len())]),
+        });
+    }
+    matches
+}
+
+/// Like `git log -S<needle>`: finds the commits in `since..rev` that change how
+/// often `needle` occurs in a file, i.e. the commits that introduced or removed
+/// it. Merges are skipped, as git does without `-m`.
+pub fn pickaxe_search(
+    repo_path: &Path,
+    rev: Option<&str>,
+    needle: &str,
+    options: &PickaxeOptions,
+) -> Result<Vec<PickaxeHit>, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let start = resolve_commit(&repo, rev.unwrap_or("HEAD"))?;
+    let matcher = TextMatcher::new(needle, options.use_regex, options.case_insensitive)?;
+    let pathspec = Pathspec::new(&options.pathspecs);
+    let diff_options = DiffOptions {
+        detect_renames: false,
+        ..DiffOptions::default()
+    };
+
+    let since = options
+        .since
+        .as_deref()
+        .map(|since| resolve_commit(&repo, since))
+        .transpose()?;
+
+    let count = |blob: Option<ObjectId>| -> Result<Option<usize>, AppError> {
+        let Some(blob) = blob else {
+            return Ok(Some(0));
+        };
+        let data = read_blob(&repo, blob)?;
+        if is_binary_content(&data) {
+            return Ok(None);
+        }
+        Ok(Some(
+            matcher.find_ranges(&String::from_utf8_lossy(&data)).count(),
+        ))
+    };
+
+    let mut hits = Vec::new();
+    let walk = repo
+        .rev_walk([start])
+        .with_hidden(since)
+        .sorting(gix::revision::walk::Sorting::ByCommitTime(
+            gix::traverse::commit::simple::CommitTimeOrder::NewestFirst,
+        ))
+        .all()
+        .map_err(|e| AppError::Git(e.to_string()))?;
+    for info in walk {
+        if hits.len() >= options.max_results {
+            break;
+        }
+        let info = info.map_err(|e| AppError::Git(e.to_string()))?;
+        if info.parent_ids.len() > 1 {
+            continue;
+        }
+
+        let oid = info.id;
+        let (parent_tree, tree) = commit_trees(&repo, oid)?;
+        let mut files = Vec::new();
+        for change in changes_between(&repo, parent_tree, tree, &diff_options)? {
+            let path = change.path().to_string();
+            if !pathspec.matches(&path) {
+                continue;
+            }
+            if let (Some(old_count), Some(new_count)) =
+                (count(change.old_id)?, count(change.new_id)?)
+            {
+                if old_count != new_count {
+                    files.push(PickaxeFile {
+                        path,
+                        old_count,
+                        new_count,
+                    });
+                }
+            }
+        }
+        if !files.is_empty() {
+            hits.push(PickaxeHit {
+                commit: parse_commit_from_repo(&repo, oid)?,
+                files,
+            });
+        }
+    }
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_repo_for_grep() -> (TempDir, TempDir, Vec<String>) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+
+        let mut commits = Vec::new();
+        std::fs::create_dir(work.join("src")).unwrap();
+        std::fs::write(work.join("src/lib.rs"), "fn parse() {}\n").unwrap();
+        std::fs::write(work.join("notes.md"), "todo: nothing\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "first"]);
+        commits.push(git(&["rev-parse", "HEAD"]));
+
+        std::fs::write(
+            work.join("src/lib.rs"),
+            "fn parse() {\n    // TODO: handle errors\n    unimplemented!()\n}\n",
+        )
+        .unwrap();
+        std::fs::write(work.join("logo.bin"), b"\0TODO").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "add todo"]);
+        commits.push(git(&["rev-parse", "HEAD"]));
+
+        std::fs::write(work.join("notes.md"), "done\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "clear notes"]);
+        commits.push(git(&["rev-parse", "HEAD"]));
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        (work_dir, bare_dir, commits)
+    }
+
+    #[test]
+    fn test_grep_tree() {
+        let (_work, bare, commits) = create_repo_for_grep();
+        let options = GrepOptions {
+            case_insensitive: true,
+            context_lines: 1,
+            ..GrepOptions::default()
+        };
+        let result = grep_tree(bare.path(), None, "todo", &options).unwrap();
+        assert_eq!(result.matches.len(), 1);
+        let found = &result.matches[0];
+        assert_eq!((found.path.as_str(), found.line_number), ("src/lib.rs", 2));
+        assert_eq!(found.match_ranges, vec![(7, 11)]);
+        assert_eq!(found.context_before, vec!["fn parse() {"]);
+        assert_eq!(found.context_after, vec!["    unimplemented!()"]);
+
+        let old = grep_tree(bare.path(), Some(&commits[0]), "todo", &options).unwrap();
+        assert_eq!(old.matches[0].path, "notes.md");
+
+        let options = GrepOptions {
+            use_regex: true,
+            pathspecs: vec!["src/".to_string()],
+            max_matches: 1,
+            ..GrepOptions::default()
+        };
+        let result = grep_tree(bare.path(), None, r"^\s*\w", &options).unwrap();
+        assert_eq!(result.matches.len(), 1);
+        assert!(result.truncated);
+        assert!(grep_tree(bare.path(), None, "(", &options).is_err());
+    }
+
+    #[test]
+    fn test_pickaxe_search() {
+        let (_work, bare, commits) = create_repo_for_grep();
+        let options = PickaxeOptions {
+            case_insensitive: true,
+            ..PickaxeOptions::default()
+        };
+        let hits = pickaxe_search(bare.path(), None, "todo", &options).unwrap();
+        let found: Vec<(&str, Vec<&str>)> = hits
+            .iter()
+            .map(|h| {
+                (
+                    h.commit.oid.as_str(),
+                    h.files.iter().map(|f| f.path.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (commits[2].as_str(), vec!["notes.md"]),
+                (commits[1].as_str(), vec!["src/lib.rs"]),
+                (commits[0].as_str(), vec!["notes.md"]),
+            ]
+        );
+        assert_eq!(
+            (hits[0].files[0].old_count, hits[0].files[0].new_count),
+            (1, 0)
+        );
+
+        let options = PickaxeOptions {
+            since: Some(commits[0].clone()),
+            pathspecs: vec!["src".to_string()],
+            ..PickaxeOptions::default()
+        };
+        let hits = pickaxe_search(bare.path(), None, "TODO", &options).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].commit.oid, commits[1]);
+    }
+}
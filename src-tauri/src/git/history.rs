@@ -0,0 +1,199 @@
+use std::path::Path;
+
+use gix::ObjectId;
+
+use crate::error::AppError;
+use crate::git::diff::{blob_at, changes_between, commit_tree_id};
+use crate::git::log::parse_commit_from_repo;
+use crate::git::repository::open_bare_repo;
+use crate::git::revision::resolve_commit;
+use crate::types::{CommitInfo, DiffOptions, FileHistoryEntry, FileStatus};
+
+/// Walks the history of a single file, following it to its old path whenever a
+/// commit turns out to have renamed it. Like git's default simplification, a
+/// commit is only listed when the file differs from every one of its parents.
+pub fn get_file_history(
+    repo_path: &Path,
+    file_path: &str,
+    start: Option<&str>,
+    limit: usize,
+) -> Result<Vec<FileHistoryEntry>, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let start_id = match start {
+        Some(spec) => resolve_commit(&repo, spec)?,
+        None => {
+            repo.head_commit()
+                .map_err(|e| AppError::Git(e.to_string()))?
+                .id
+        }
+    };
+
+    let walk = repo
+        .rev_walk([start_id])
+        .all()
+        .map_err(|e| AppError::Git(e.to_string()))?;
+
+    let mut path = file_path.trim_matches('/').to_string();
+    let mut entries = Vec::new();
+
+    for info in walk {
+        if entries.len() >= limit {
+            break;
+        }
+        let info = info.map_err(|e| AppError::Git(e.to_string()))?;
+        let commit = parse_commit_from_repo(&repo, info.id)?;
+
+        let blob = blob_at(&repo, info.id, &path)?;
+        let parent_ids = parent_ids(&commit)?;
+        let mut parent_blobs = Vec::with_capacity(parent_ids.len());
+        for parent in &parent_ids {
+            parent_blobs.push(blob_at(&repo, *parent, &path)?);
+        }
+        if parent_blobs.contains(&blob) {
+            continue;
+        }
+
+        let first_parent = parent_ids.first().copied();
+        let (status, old_path) = match (blob, parent_blobs.first().copied().flatten()) {
+            (None, None) if parent_blobs.is_empty() => continue,
+            (None, _) => (FileStatus::Deleted, None),
+            (Some(_), Some(_)) => (FileStatus::Modified, None),
+            (Some(_), None) => match first_parent {
+                Some(parent) => find_rename_source(&repo, parent, info.id, &path)?,
+                None => (FileStatus::Added, None),
+            },
+        };
+
+        entries.push(FileHistoryEntry {
+            commit,
+            path: path.clone(),
+            old_path: old_path.clone(),
+            blob_oid: blob.map(|id| id.to_string()),
+            status,
+        });
+        if let Some(old_path) = old_path {
+            path = old_path;
+        }
+    }
+
+    Ok(entries)
+}
+
+fn parent_ids(commit: &CommitInfo) -> Result<Vec<ObjectId>, AppError> {
+    commit
+        .parent_oids
+        .iter()
+        .map(|oid| {
+            ObjectId::from_hex(oid.as_bytes())
+                .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))
+        })
+        .collect()
+}
+
+/// Only runs rename detection for the commit that introduced the path, so the
+/// cost is paid once per rename rather than once per commit.
+pub fn find_rename_source(
+    repo: &gix::Repository,
+    parent: ObjectId,
+    commit: ObjectId,
+    path: &str,
+) -> Result<(FileStatus, Option<String>), AppError> {
+    let options = DiffOptions {
+        detect_copies: true,
+        ..DiffOptions::default()
+    };
+    let changes = changes_between(
+        repo,
+        Some(commit_tree_id(repo, parent)?),
+        commit_tree_id(repo, commit)?,
+        &options,
+    )?;
+
+    let source = changes.into_iter().find(|change| {
+        change.new_path.as_deref() == Some(path)
+            && matches!(change.status, FileStatus::Renamed | FileStatus::Copied)
+    });
+    Ok(match source {
+        Some(change) => (change.status, change.old_path),
+        None => (FileStatus::Added, None),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_repo_with_rename() -> (TempDir, TempDir) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+        };
+        git(&["init"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+
+        let body: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+        std::fs::write(work.join("old.txt"), &body).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "add old"]);
+
+        std::fs::write(work.join("other.txt"), "unrelated\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "unrelated"]);
+
+        std::fs::write(work.join("old.txt"), body.replace("line 1\n", "line one\n")).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "edit old"]);
+
+        git(&["mv", "old.txt", "new.txt"]);
+        git(&["commit", "-m", "rename"]);
+
+        std::fs::write(work.join("new.txt"), body.replace("line 2\n", "line two\n")).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "edit new"]);
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        (work_dir, bare_dir)
+    }
+
+    #[test]
+    fn test_get_file_history_follows_renames() {
+        let (_work, bare) = create_repo_with_rename();
+        let history = get_file_history(bare.path(), "new.txt", None, 50).unwrap();
+
+        let summary: Vec<(&str, &str, FileStatus)> = history
+            .iter()
+            .map(|e| (e.commit.message.trim(), e.path.as_str(), e.status.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("edit new", "new.txt", FileStatus::Modified),
+                ("rename", "new.txt", FileStatus::Renamed),
+                ("edit old", "old.txt", FileStatus::Modified),
+                ("add old", "old.txt", FileStatus::Added),
+            ]
+        );
+        assert_eq!(history[1].old_path.as_deref(), Some("old.txt"));
+        assert_eq!(history[1].blob_oid, history[2].blob_oid);
+        assert!(history.iter().all(|e| e.blob_oid.is_some()));
+
+        let limited = get_file_history(bare.path(), "new.txt", None, 2).unwrap();
+        assert_eq!(limited.len(), 2);
+    }
+}
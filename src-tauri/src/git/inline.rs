@@ -0,0 +1,143 @@
+use similar::{ChangeTag, TextDiff};
+
+use crate::types::{DiffLine, DiffLineKind, InlineGranularity, InlineSpan};
+
+/// Lines longer than this are left without inline spans; diffing them by
+/// character gets expensive and the result is rarely readable anyway.
+const MAX_INLINE_LINE_LEN: usize = 1024;
+
+/// Fills in `spans` for modified lines of a hunk. Each run of deletions that is
+/// directly followed by a run of additions is paired up line by line, the same
+/// way most diff viewers decide which lines are "the same line, edited".
+pub fn annotate_inline_changes(lines: &mut [DiffLine], granularity: InlineGranularity) {
+    if granularity == InlineGranularity::None {
+        return;
+    }
+
+    let mut i = 0;
+    while i < lines.len() {
+        let deletions_start = i;
+        while i < lines.len() && lines[i].kind == DiffLineKind::Deletion {
+            i += 1;
+        }
+        let additions_start = i;
+        while i < lines.len() && lines[i].kind == DiffLineKind::Addition {
+            i += 1;
+        }
+
+        let deletions = additions_start - deletions_start;
+        let additions = i - additions_start;
+        for offset in 0..deletions.min(additions) {
+            let old_index = deletions_start + offset;
+            let new_index = additions_start + offset;
+            if let Some((old_spans, new_spans)) = inline_spans(
+                &lines[old_index].content,
+                &lines[new_index].content,
+                granularity,
+            ) {
+                lines[old_index].spans = old_spans;
+                lines[new_index].spans = new_spans;
+            }
+        }
+
+        if deletions == 0 && additions == 0 {
+            i += 1;
+        }
+    }
+}
+
+fn inline_spans(
+    old: &str,
+    new: &str,
+    granularity: InlineGranularity,
+) -> Option<(Vec<InlineSpan>, Vec<InlineSpan>)> {
+    if old.len() > MAX_INLINE_LINE_LEN || new.len() > MAX_INLINE_LINE_LEN {
+        return None;
+    }
+
+    let diff = match granularity {
+        InlineGranularity::Char => TextDiff::from_chars(old, new),
+        _ => TextDiff::from_words(old, new),
+    };
+
+    let mut old_spans = Vec::new();
+    let mut new_spans = Vec::new();
+    for change in diff.iter_all_changes() {
+        let value = change.value();
+        match change.tag() {
+            ChangeTag::Equal => {
+                push_span(&mut old_spans, value, false);
+                push_span(&mut new_spans, value, false);
+            }
+            ChangeTag::Delete => push_span(&mut old_spans, value, true),
+            ChangeTag::Insert => push_span(&mut new_spans, value, true),
+        }
+    }
+
+    Some((old_spans, new_spans))
+}
+
+fn push_span(spans: &mut Vec<InlineSpan>, text: &str, changed: bool) {
+    match spans.last_mut() {
+        Some(last) if last.changed == changed => last.text.push_str(text),
+        _ => spans.push(InlineSpan {
+            text: text.to_string(),
+            changed,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(kind: DiffLineKind, content: &str) -> DiffLine {
+        DiffLine {
+            kind,
+            old_line: None,
+            new_line: None,
+            content: content.to_string(),
+            missing_newline: false,
+            spans: Vec::new(),
+        }
+    }
+
+    fn changed_text(spans: &[InlineSpan]) -> Vec<&str> {
+        spans
+            .iter()
+            .filter(|s| s.changed)
+            .map(|s| s.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_annotate_inline_changes_words() {
+        let mut lines = vec![
+            line(DiffLineKind::Context, "fn main() {"),
+            line(DiffLineKind::Deletion, "let total = a + b;"),
+            line(DiffLineKind::Addition, "let total = a + c;"),
+            line(DiffLineKind::Addition, "println!(total);"),
+        ];
+        annotate_inline_changes(&mut lines, InlineGranularity::Word);
+
+        assert!(lines[0].spans.is_empty());
+        assert_eq!(changed_text(&lines[1].spans), vec!["b;"]);
+        assert_eq!(changed_text(&lines[2].spans), vec!["c;"]);
+        assert!(lines[3].spans.is_empty());
+
+        let joined: String = lines[2].spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(joined, lines[2].content);
+    }
+
+    #[test]
+    fn test_annotate_inline_changes_chars() {
+        let mut lines = vec![
+            line(DiffLineKind::Deletion, "colour"),
+            line(DiffLineKind::Addition, "color"),
+        ];
+        annotate_inline_changes(&mut lines, InlineGranularity::Char);
+
+        assert_eq!(changed_text(&lines[0].spans), vec!["u"]);
+        assert!(changed_text(&lines[1].spans).is_empty());
+    }
+}
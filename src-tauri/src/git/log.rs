@@ -1,42 +1,18 @@
 use std::path::Path;
 
 use gix::ObjectId;
+use regex::{Regex, RegexBuilder};
 
 use crate::error::AppError;
+use crate::git::actor::decode_signature;
+use crate::git::diff::{commit_tree_id, lookup_path};
+use crate::git::message::{decode_message, parse_message};
 use crate::git::repository::open_bare_repo;
-use crate::types::{CommitInfo, CommitLogPage};
+use crate::git::revision::resolve_commit;
+use crate::git::walk::CommitWalk;
+use crate::types::{CommitInfo, CommitLogPage, LogFilter, LogSort};
 
-struct ParsedSignature {
-    name: String,
-    email: String,
-    timestamp: i64,
-}
-
-fn parse_signature(raw: &str) -> ParsedSignature {
-    match (raw.find('<'), raw.find('>')) {
-        (Some(lt), Some(gt)) if lt < gt => {
-            let name = raw[..lt].trim().to_string();
-            let email = raw[lt + 1..gt].to_string();
-            let timestamp = raw[gt + 1..]
-                .split_whitespace()
-                .next()
-                .and_then(|t| t.parse().ok())
-                .unwrap_or(0);
-            ParsedSignature {
-                name,
-                email,
-                timestamp,
-            }
-        }
-        _ => ParsedSignature {
-            name: raw.to_string(),
-            email: String::new(),
-            timestamp: 0,
-        },
-    }
-}
-
-fn parse_commit_from_repo(
+pub fn parse_commit_from_repo(
     repo: &gix::Repository,
     oid: ObjectId,
 ) -> Result<CommitInfo, AppError> {
@@ -47,72 +23,238 @@ fn parse_commit_from_repo(
     let commit_ref = commit.decode().map_err(|e| AppError::Git(e.to_string()))?;
 
     let short_oid = oid.to_hex_with_len(7).to_string();
-    let author = parse_signature(&commit_ref.author.to_string());
-    let committer = parse_signature(&commit_ref.committer.to_string());
+    let author = decode_signature(
+        commit_ref
+            .author()
+            .map_err(|e| AppError::Git(e.to_string()))?,
+    );
+    let committer = decode_signature(
+        commit_ref
+            .committer()
+            .map_err(|e| AppError::Git(e.to_string()))?,
+    );
+
+    let message = decode_message(commit_ref.message, commit_ref.encoding.map(|e| e.as_ref()));
+    let parsed = parse_message(&message);
 
     Ok(CommitInfo {
         oid: oid.to_string(),
         short_oid,
-        message: commit_ref.message.to_string(),
+        message,
+        subject: parsed.subject,
+        body: parsed.body,
+        trailers: parsed.trailers,
         author_name: author.name,
         author_email: author.email,
         committer_name: committer.name,
         committer_email: committer.email,
         parent_oids: commit_ref.parents().map(|p| p.to_string()).collect(),
         timestamp: author.timestamp,
+        author_utc_offset: author.utc_offset,
+        committer_timestamp: committer.timestamp,
+        committer_utc_offset: committer.utc_offset,
+        is_signed: commit_ref
+            .extra_headers
+            .iter()
+            .any(|(name, _)| *name == "gpgsig" || *name == "gpgsig-sha256"),
     })
 }
 
+/// A substring or regex pattern, optionally case-insensitive like
+/// `git log -i --grep`.
+pub struct TextMatcher(Regex);
+
+impl TextMatcher {
+    pub fn new(pattern: &str, use_regex: bool, case_insensitive: bool) -> Result<Self, AppError> {
+        let escaped;
+        let source = if use_regex {
+            pattern
+        } else {
+            escaped = regex::escape(pattern);
+            &escaped
+        };
+        // Multi-line so `^` and `$` anchor to lines, as with `git log --grep`.
+        RegexBuilder::new(source)
+            .case_insensitive(case_insensitive)
+            .multi_line(true)
+            .build()
+            .map(TextMatcher)
+            .map_err(|e| AppError::Git(format!("Invalid pattern '{pattern}': {e}")))
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+
+    /// Byte ranges of the non-overlapping matches in `text`.
+    pub fn find_ranges<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.0.find_iter(text).map(|m| (m.start(), m.end()))
+    }
+}
+
+/// How many commits older than `since` are still walked before giving up, like
+/// git's `SLOP`.
+const SINCE_SLOP: usize = 5;
+
+/// A `LogFilter` with its patterns compiled once for the whole walk.
+struct CommitFilter {
+    author: Option<TextMatcher>,
+    committer: Option<TextMatcher>,
+    message: Option<TextMatcher>,
+    since: Option<i64>,
+    until: Option<i64>,
+    path: Option<String>,
+}
+
+impl CommitFilter {
+    fn new(filter: &LogFilter) -> Result<Self, AppError> {
+        let compile = |pattern: &Option<String>| {
+            pattern
+                .as_deref()
+                .filter(|p| !p.is_empty())
+                .map(|p| TextMatcher::new(p, filter.use_regex, filter.case_insensitive))
+                .transpose()
+        };
+        Ok(Self {
+            author: compile(&filter.author)?,
+            committer: compile(&filter.committer)?,
+            message: compile(&filter.message)?,
+            since: filter.since,
+            until: filter.until,
+            path: filter
+                .path
+                .as_deref()
+                .map(|p| p.trim_matches('/').to_string())
+                .filter(|p| !p.is_empty()),
+        })
+    }
+
+    /// Cheap checks run first; the path check needs tree lookups.
+    fn matches(&self, repo: &gix::Repository, commit: &CommitInfo) -> Result<bool, AppError> {
+        let identity = |name: &str, email: &str| format!("{name} <{email}>");
+        if self
+            .since
+            .is_some_and(|since| commit.committer_timestamp < since)
+            || self
+                .until
+                .is_some_and(|until| commit.committer_timestamp > until)
+        {
+            return Ok(false);
+        }
+        if let Some(author) = &self.author {
+            if !author.is_match(&identity(&commit.author_name, &commit.author_email)) {
+                return Ok(false);
+            }
+        }
+        if let Some(committer) = &self.committer {
+            if !committer.is_match(&identity(&commit.committer_name, &commit.committer_email)) {
+                return Ok(false);
+            }
+        }
+        if let Some(message) = &self.message {
+            if !message.is_match(&commit.message) {
+                return Ok(false);
+            }
+        }
+        match &self.path {
+            Some(path) => touches_path(repo, commit, path),
+            None => Ok(true),
+        }
+    }
+}
+
+/// Like git's default history simplification, a commit touches `path` when the
+/// entry there differs from every parent, so merges that took one side's
+/// version unchanged are skipped.
+pub fn touches_path(repo: &gix::Repository, commit: &CommitInfo, path: &str) -> Result<bool, AppError> {
+    let oid = ObjectId::from_hex(commit.oid.as_bytes())
+        .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?;
+    let entry = lookup_path(repo, commit_tree_id(repo, oid)?, path)?;
+    if commit.parent_oids.is_empty() {
+        return Ok(entry.is_some());
+    }
+
+    for parent in &commit.parent_oids {
+        let parent_oid = ObjectId::from_hex(parent.as_bytes())
+            .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?;
+        if lookup_path(repo, commit_tree_id(repo, parent_oid)?, path)? == entry {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Returns one page of the log. The first page starts at `start_oid` (HEAD by
+/// default) and walks in `sort` order; later pages pass the previous page's
+/// `cursor`, which resumes the walk with the sort it was started with.
 pub fn get_commit_log(
     repo_path: &Path,
     start_oid: Option<&str>,
+    cursor: Option<&str>,
     limit: usize,
+    filter: &LogFilter,
+    sort: LogSort,
 ) -> Result<CommitLogPage, AppError> {
     let repo = open_bare_repo(repo_path)?;
+    let filter = CommitFilter::new(filter)?;
 
-    let start_id = match start_oid {
-        Some(oid_str) => ObjectId::from_hex(oid_str.as_bytes())
-            .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?,
+    let mut walk = match cursor {
+        Some(cursor) => CommitWalk::resume(&repo, cursor)?,
         None => {
-            let head = repo.head_commit().map_err(|e| AppError::Git(e.to_string()))?;
-            head.id
+            let start_id = match start_oid {
+                Some(spec) => resolve_commit(&repo, spec)?,
+                None => {
+                    let head = repo
+                        .head_commit()
+                        .map_err(|e| AppError::Git(e.to_string()))?;
+                    head.id
+                }
+            };
+            CommitWalk::new(&repo, &[start_id], sort)?
         }
     };
 
-    let walk = repo
-        .rev_walk([start_id])
-        .all()
-        .map_err(|e| AppError::Git(e.to_string()))?;
-
     let mut commits = Vec::with_capacity(limit);
-    let fetch_count = limit + 1;
-
-    for info in walk {
-        if commits.len() >= fetch_count {
+    let mut past_since = false;
+    let mut slop = SINCE_SLOP;
+    loop {
+        // Like `git log --since`, a walk in committer date order stops once
+        // everything left is older, after a few more commits in case of skew.
+        if let (Some(since), Some(date)) = (filter.since, walk.next_committer_date()) {
+            if date >= since {
+                slop = SINCE_SLOP;
+            } else if slop == 0 {
+                past_since = true;
+                break;
+            } else {
+                slop -= 1;
+            }
+        }
+        let Some(commit) = walk.next_commit()? else {
             break;
+        };
+        if !filter.matches(&repo, &commit)? {
+            continue;
         }
-        let info = info.map_err(|e| AppError::Git(e.to_string()))?;
-        commits.push(parse_commit_from_repo(&repo, info.id)?);
-    }
-
-    let has_more = commits.len() > limit;
-    if has_more {
-        commits.truncate(limit);
+        // Only report more pages if another matching commit actually exists.
+        if commits.len() >= limit {
+            walk.push_back(commit)?;
+            break;
+        }
+        commits.push(commit);
     }
 
-    let cursor = commits.last().map(|c| c.oid.clone());
-
+    let cursor = if past_since { None } else { walk.into_cursor() };
     Ok(CommitLogPage {
         commits,
-        has_more,
+        has_more: cursor.is_some(),
         cursor,
     })
 }
 
 pub fn get_commit_detail(repo_path: &Path, oid_str: &str) -> Result<CommitInfo, AppError> {
     let repo = open_bare_repo(repo_path)?;
-    let oid = ObjectId::from_hex(oid_str.as_bytes())
-        .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?;
+    let oid = resolve_commit(&repo, oid_str)?;
 
     parse_commit_from_repo(&repo, oid)
 }
@@ -120,6 +262,7 @@ pub fn get_commit_detail(repo_path: &Path, oid_str: &str) -> Result<CommitInfo,
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::LogOrder;
     use std::process::Command;
     use tempfile::TempDir;
 
@@ -180,7 +323,9 @@ mod tests {
     #[test]
     fn test_get_commit_log() {
         let (_work, bare) = create_repo_with_commits();
-        let page = get_commit_log(bare.path(), None, 10).unwrap();
+        let filter = LogFilter::default();
+        let page =
+            get_commit_log(bare.path(), None, None, 10, &filter, LogSort::default()).unwrap();
         assert_eq!(page.commits.len(), 2);
         assert!(!page.has_more);
     }
@@ -188,16 +333,403 @@ mod tests {
     #[test]
     fn test_get_commit_log_pagination() {
         let (_work, bare) = create_repo_with_commits();
-        let page = get_commit_log(bare.path(), None, 1).unwrap();
+        let filter = LogFilter::default();
+        let page = get_commit_log(bare.path(), None, None, 1, &filter, LogSort::default()).unwrap();
         assert_eq!(page.commits.len(), 1);
         assert!(page.has_more);
     }
 
+    /// Three commits by different authors a day apart; only the first and last
+    /// touch `src/lib.rs`.
+    fn create_repo_with_authors() -> (TempDir, TempDir) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str], author: &str, date: &str| {
+            Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .env("GIT_AUTHOR_NAME", author)
+                .env(
+                    "GIT_AUTHOR_EMAIL",
+                    format!("{}@example.com", author.to_lowercase()),
+                )
+                .env("GIT_COMMITTER_NAME", "Maintainer")
+                .env("GIT_COMMITTER_EMAIL", "maintainer@example.com")
+                .env("GIT_AUTHOR_DATE", date)
+                .env("GIT_COMMITTER_DATE", date)
+                .output()
+                .unwrap();
+        };
+        git(&["init"], "", "");
+
+        let commits = [
+            (
+                "Alice",
+                "1700000000 +0000",
+                "src/lib.rs",
+                "feat: add parser",
+            ),
+            ("Bob", "1700086400 +0530", "README.md", "docs: readme"),
+            (
+                "Alice",
+                "1700172800 +0000",
+                "src/lib.rs",
+                "fix: parser bug #12",
+            ),
+        ];
+        for (author, date, path, message) in commits {
+            let file = work.join(path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(&file, message).unwrap();
+            git(&["add", "."], author, date);
+            git(&["commit", "-m", message], author, date);
+        }
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        (work_dir, bare_dir)
+    }
+
+    fn messages(page: &CommitLogPage) -> Vec<&str> {
+        page.commits.iter().map(|c| c.message.trim()).collect()
+    }
+
+    #[test]
+    fn test_get_commit_log_filters() {
+        let (_work, bare) = create_repo_with_authors();
+        let log = |filter: LogFilter| {
+            get_commit_log(bare.path(), None, None, 10, &filter, LogSort::default()).unwrap()
+        };
+
+        let page = log(LogFilter {
+            author: Some("alice".to_string()),
+            ..LogFilter::default()
+        });
+        assert_eq!(
+            messages(&page),
+            vec!["fix: parser bug #12", "feat: add parser"]
+        );
+
+        let page = log(LogFilter {
+            author: Some("ALICE".to_string()),
+            ..LogFilter::default()
+        });
+        assert!(page.commits.is_empty());
+
+        let page = log(LogFilter {
+            author: Some("ALICE".to_string()),
+            case_insensitive: true,
+            ..LogFilter::default()
+        });
+        assert_eq!(page.commits.len(), 2);
+
+        let page = log(LogFilter {
+            message: Some(r"^fix: .*#\d+$".to_string()),
+            use_regex: true,
+            ..LogFilter::default()
+        });
+        assert_eq!(messages(&page), vec!["fix: parser bug #12"]);
+
+        let page = log(LogFilter {
+            since: Some(1700050000),
+            until: Some(1700100000),
+            ..LogFilter::default()
+        });
+        assert_eq!(messages(&page), vec!["docs: readme"]);
+
+        let page = log(LogFilter {
+            path: Some("src".to_string()),
+            ..LogFilter::default()
+        });
+        assert_eq!(
+            messages(&page),
+            vec!["fix: parser bug #12", "feat: add parser"]
+        );
+
+        let page = log(LogFilter {
+            committer: Some("nobody".to_string()),
+            ..LogFilter::default()
+        });
+        assert!(page.commits.is_empty());
+
+        let invalid = LogFilter {
+            author: Some("(".to_string()),
+            use_regex: true,
+            ..LogFilter::default()
+        };
+        assert!(get_commit_log(bare.path(), None, None, 10, &invalid, LogSort::default()).is_err());
+    }
+
+    #[test]
+    fn test_get_commit_log_filtered_pagination() {
+        let (_work, bare) = create_repo_with_authors();
+        let filter = LogFilter {
+            author: Some("alice".to_string()),
+            ..LogFilter::default()
+        };
+
+        let first =
+            get_commit_log(bare.path(), None, None, 1, &filter, LogSort::default()).unwrap();
+        assert_eq!(messages(&first), vec!["fix: parser bug #12"]);
+        assert!(first.has_more);
+
+        let cursor = first.cursor.as_deref();
+        let second =
+            get_commit_log(bare.path(), None, cursor, 1, &filter, LogSort::default()).unwrap();
+        assert_eq!(messages(&second), vec!["feat: add parser"]);
+        assert!(!second.has_more);
+        assert!(second.cursor.is_none());
+    }
+
+    /// Two branches merged into main twice, with equal and skewed commit dates.
+    fn create_repo_with_merges() -> (TempDir, TempDir) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str], date: &str| {
+            Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .env("GIT_AUTHOR_DATE", date)
+                .env("GIT_COMMITTER_DATE", date)
+                .output()
+                .unwrap();
+        };
+        let commit = |name: &str, date: &str| {
+            std::fs::write(work.join(name), date).unwrap();
+            git(&["add", "."], date);
+            git(&["commit", "-m", name], date);
+        };
+        git(&["init", "-b", "main"], "");
+        git(&["config", "user.name", "Test"], "");
+        git(&["config", "user.email", "test@test.com"], "");
+
+        commit("c1", "1700000100 +0000");
+        git(&["checkout", "-b", "feature"], "");
+        commit("f1", "1700000300 +0000");
+        commit("f2", "1700000400 +0000");
+        git(&["checkout", "main"], "");
+        commit("m1", "1700000300 +0000");
+        commit("m2", "1700000500 +0000");
+        git(
+            &["merge", "--no-ff", "-m", "merge 1", "feature"],
+            "1700000600 +0000",
+        );
+        git(&["checkout", "feature"], "");
+        commit("f3", "1700000150 +0000");
+        git(&["checkout", "main"], "");
+        commit("m3", "1700000650 +0000");
+        git(
+            &["merge", "--no-ff", "-m", "merge 2", "feature"],
+            "1700000700 +0000",
+        );
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        (work_dir, bare_dir)
+    }
+
+    /// Pages through the whole log `limit` commits at a time.
+    fn page_through(repo: &Path, limit: usize, sort: LogSort) -> Vec<String> {
+        let filter = LogFilter::default();
+        let mut paged = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = get_commit_log(repo, None, cursor.as_deref(), limit, &filter, sort).unwrap();
+            assert!(page.commits.len() <= limit);
+            paged.extend(page.commits.into_iter().map(|c| c.oid));
+            if !page.has_more {
+                return paged;
+            }
+            cursor = page.cursor;
+        }
+    }
+
+    fn log_messages(repo: &Path, sort: LogSort) -> Vec<String> {
+        let page = get_commit_log(repo, None, None, 100, &LogFilter::default(), sort).unwrap();
+        page.commits
+            .into_iter()
+            .map(|c| c.message.trim().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_get_commit_log_cursor_resumes_walk() {
+        let (_work, bare) = create_repo_with_merges();
+        let filter = LogFilter::default();
+        let full =
+            get_commit_log(bare.path(), None, None, 100, &filter, LogSort::default()).unwrap();
+        assert_eq!(full.commits.len(), 9);
+        assert!(!full.has_more);
+        assert!(full.cursor.is_none());
+        let full: Vec<String> = full.commits.into_iter().map(|c| c.oid).collect();
+
+        for limit in 1..=4 {
+            let paged = page_through(bare.path(), limit, LogSort::default());
+            assert_eq!(paged, full, "limit {limit}");
+        }
+    }
+
+    #[test]
+    fn test_get_commit_log_sort_modes() {
+        let (_work, bare) = create_repo_with_merges();
+        let sort = |order, reverse, first_parent| LogSort {
+            order,
+            reverse,
+            first_parent,
+        };
+
+        let topo = log_messages(bare.path(), sort(LogOrder::Topological, false, false));
+        assert_eq!(
+            topo,
+            vec!["merge 2", "f3", "m3", "merge 1", "f2", "f1", "m2", "m1", "c1"]
+        );
+
+        let mut reversed = log_messages(bare.path(), sort(LogOrder::CommitterDate, true, false));
+        reversed.reverse();
+        assert_eq!(reversed, log_messages(bare.path(), LogSort::default()));
+
+        let first_parent = log_messages(bare.path(), sort(LogOrder::CommitterDate, false, true));
+        assert_eq!(
+            first_parent,
+            vec!["merge 2", "m3", "merge 1", "m2", "m1", "c1"]
+        );
+
+        for sort in [
+            sort(LogOrder::Topological, false, false),
+            sort(LogOrder::AuthorDate, true, false),
+            sort(LogOrder::AuthorDate, false, true),
+        ] {
+            let full = page_through(bare.path(), 100, sort);
+            for limit in 1..=3 {
+                assert_eq!(page_through(bare.path(), limit, sort), full, "{sort:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_commit_signatures_keep_offsets() {
+        let (_work, bare) = create_repo_with_authors();
+        let filter = LogFilter {
+            author: Some("bob".to_string()),
+            ..LogFilter::default()
+        };
+        let page =
+            get_commit_log(bare.path(), None, None, 10, &filter, LogSort::default()).unwrap();
+        let bob = &page.commits[0];
+        assert_eq!(bob.author_email, "bob@example.com");
+        assert_eq!(bob.committer_name, "Maintainer");
+        assert_eq!(bob.timestamp, 1700086400);
+        assert_eq!(bob.committer_timestamp, 1700086400);
+        assert_eq!(bob.author_utc_offset, 5 * 3600 + 30 * 60);
+        assert_eq!(bob.committer_utc_offset, 5 * 3600 + 30 * 60);
+    }
+
+    #[test]
+    fn test_commit_message_structure_and_encoding() {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+        };
+        git(&["init"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "i18n.commitEncoding", "ISO-8859-1"]);
+
+        std::fs::write(work.join("file.txt"), "hello").unwrap();
+        std::fs::write(
+            work.join("msg"),
+            b"Caf\xe9 menu\n\nPrices in \xe9cus.\n\nReviewed-by: Ren\xe9e\nFixes: #7\n",
+        )
+        .unwrap();
+        git(&["add", "file.txt"]);
+        git(&["commit", "-F", "msg"]);
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+
+        let filter = LogFilter::default();
+        let page =
+            get_commit_log(bare_dir.path(), None, None, 1, &filter, LogSort::default()).unwrap();
+        let commit = &page.commits[0];
+        assert_eq!(commit.subject, "Café menu");
+        assert_eq!(commit.body, "Prices in écus.");
+        let trailers: Vec<(&str, &str)> = commit
+            .trailers
+            .iter()
+            .map(|t| (t.key.as_str(), t.value.as_str()))
+            .collect();
+        assert_eq!(trailers, vec![("Reviewed-by", "Renée"), ("Fixes", "#7")]);
+    }
+
     #[test]
-    fn test_parse_signature() {
-        let sig = parse_signature("Test User <test@example.com> 1234567890 +0000");
-        assert_eq!(sig.name, "Test User");
-        assert_eq!(sig.email, "test@example.com");
-        assert_eq!(sig.timestamp, 1234567890);
+    fn test_since_stops_the_walk() {
+        let dir = TempDir::new().unwrap();
+        let git = |args: &[&str], time: i64| {
+            let date = format!("@{time} +0000");
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(args)
+                .env("GIT_AUTHOR_NAME", "Test")
+                .env("GIT_AUTHOR_EMAIL", "test@test.com")
+                .env("GIT_COMMITTER_NAME", "Test")
+                .env("GIT_COMMITTER_EMAIL", "test@test.com")
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init", "--bare"], 0);
+        let tree = git(&["hash-object", "-t", "tree", "-w", "/dev/null"], 0);
+        let mut parent: Option<String> = None;
+        for day in 0..50 {
+            let mut args = vec!["commit-tree", &tree, "-m", "day"];
+            if let Some(parent) = &parent {
+                args.extend(["-p", parent]);
+            }
+            parent = Some(git(&args, 1700000000 + day * 86400));
+        }
+        git(&["update-ref", "HEAD", parent.as_deref().unwrap()], 0);
+
+        let filter = LogFilter {
+            since: Some(1700000000 + 45 * 86400),
+            ..LogFilter::default()
+        };
+        crate::git::walk::COMMITS_WALKED.with(|count| count.set(0));
+        let page =
+            get_commit_log(dir.path(), None, None, 100, &filter, LogSort::default()).unwrap();
+        assert_eq!(page.commits.len(), 5);
+        assert!(!page.has_more);
+        assert_eq!(
+            crate::git::walk::COMMITS_WALKED.with(|count| count.get()),
+            5 + SINCE_SLOP
+        );
     }
 }
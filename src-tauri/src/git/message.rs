@@ -0,0 +1,149 @@
+use encoding_rs::Encoding;
+
+use crate::types::CommitTrailer;
+
+/// Decodes a raw commit message using the commit's `encoding` header. Without
+/// one, or with a label we don't know, the message is read as UTF-8 like git
+/// does, replacing invalid sequences.
+pub fn decode_message(raw: &[u8], encoding: Option<&[u8]>) -> String {
+    match encoding.and_then(Encoding::for_label) {
+        Some(encoding) => encoding.decode_without_bom_handling(raw).0.into_owned(),
+        None => String::from_utf8_lossy(raw).into_owned(),
+    }
+}
+
+pub struct ParsedMessage {
+    pub subject: String,
+    pub body: String,
+    pub trailers: Vec<CommitTrailer>,
+}
+
+/// Splits a message the way `git log --format=%s/%b` and
+/// `git interpret-trailers` see it: the subject is the first paragraph joined
+/// onto one line, and a final paragraph made only of `Key: value` lines is
+/// taken out of the body as trailers.
+pub fn parse_message(message: &str) -> ParsedMessage {
+    let paragraphs = paragraphs(message);
+    let Some((subject, rest)) = paragraphs.split_first() else {
+        return ParsedMessage {
+            subject: String::new(),
+            body: String::new(),
+            trailers: Vec::new(),
+        };
+    };
+
+    let mut rest = rest.to_vec();
+    let trailers = match rest.last().and_then(|last| parse_trailers(last)) {
+        Some(trailers) => {
+            rest.pop();
+            trailers
+        }
+        None => Vec::new(),
+    };
+
+    ParsedMessage {
+        subject: subject
+            .iter()
+            .map(|line| line.trim())
+            .collect::<Vec<_>>()
+            .join(" "),
+        body: rest
+            .iter()
+            .map(|paragraph| paragraph.join("\n"))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        trailers,
+    }
+}
+
+fn paragraphs(message: &str) -> Vec<Vec<&str>> {
+    let mut paragraphs = Vec::new();
+    let mut current = Vec::new();
+    for line in message.lines().map(str::trim_end) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs
+}
+
+/// Returns `None` unless every line is a trailer or an indented continuation
+/// of the one before it.
+fn parse_trailers(lines: &[&str]) -> Option<Vec<CommitTrailer>> {
+    let mut trailers: Vec<CommitTrailer> = Vec::new();
+    for line in lines {
+        if line.starts_with(char::is_whitespace) {
+            let last = trailers.last_mut()?;
+            last.value.push(' ');
+            last.value.push_str(line.trim());
+            continue;
+        }
+        let (key, value) = line.split_once(':')?;
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return None;
+        }
+        trailers.push(CommitTrailer {
+            key: key.to_string(),
+            value: value.trim().to_string(),
+        });
+    }
+    Some(trailers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_message() {
+        let parsed = parse_message(
+            "Fix parser\ncrash on empty input\n\nThe lexer returned None: handle it.\n\n\
+             Second paragraph.\n\nFixes: #12\nSigned-off-by: A <a@example.com>\n\
+             Co-authored-by: B\n  <b@example.com>\n",
+        );
+        assert_eq!(parsed.subject, "Fix parser crash on empty input");
+        assert_eq!(
+            parsed.body,
+            "The lexer returned None: handle it.\n\nSecond paragraph."
+        );
+        let trailers: Vec<(&str, &str)> = parsed
+            .trailers
+            .iter()
+            .map(|t| (t.key.as_str(), t.value.as_str()))
+            .collect();
+        assert_eq!(
+            trailers,
+            vec![
+                ("Fixes", "#12"),
+                ("Signed-off-by", "A <a@example.com>"),
+                ("Co-authored-by", "B <b@example.com>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_message_without_trailers() {
+        let parsed = parse_message("Subject only\n");
+        assert_eq!(parsed.subject, "Subject only");
+        assert!(parsed.body.is_empty());
+        assert!(parsed.trailers.is_empty());
+
+        let parsed = parse_message("Subject\n\nSee: the docs\nfor details");
+        assert_eq!(parsed.body, "See: the docs\nfor details");
+        assert!(parsed.trailers.is_empty());
+    }
+
+    #[test]
+    fn test_decode_message() {
+        assert_eq!(decode_message(b"caf\xe9", Some(b"ISO-8859-1")), "café");
+        assert_eq!(decode_message("café".as_bytes(), None), "café");
+        assert_eq!(decode_message(b"caf\xe9", Some(b"no-such")), "caf\u{fffd}");
+    }
+}
@@ -1,7 +1,25 @@
+pub mod actor;
+pub mod archive;
+pub mod attributes;
+pub mod blame;
+pub mod branches;
 pub mod clone;
+pub mod combined;
 pub mod dag;
 pub mod diff;
+pub mod grep;
+pub mod history;
 pub mod init;
+pub mod inline;
 pub mod log;
+pub mod message;
+pub mod pathspec;
 pub mod refs;
+pub mod rename;
 pub mod repository;
+pub mod revision;
+pub mod search;
+pub mod stats;
+pub mod tree;
+pub mod verify;
+pub mod walk;
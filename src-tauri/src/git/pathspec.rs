@@ -0,0 +1,61 @@
+use gix::glob::wildmatch;
+
+/// A small subset of git pathspecs: a pattern matches a path exactly, any path
+/// below it when it names a directory, or the path as a glob (`*` crosses `/`).
+pub struct Pathspec {
+    patterns: Vec<String>,
+}
+
+impl Pathspec {
+    pub fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .map(|p| p.trim_start_matches("./").trim_end_matches('/').to_string())
+                .filter(|p| !p.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// An empty pathspec matches everything.
+    pub fn matches(&self, path: &str) -> bool {
+        self.is_empty()
+            || self.patterns.iter().any(|pattern| {
+                path == pattern
+                    || path
+                        .strip_prefix(pattern.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+                    || wildmatch(
+                        pattern.as_str().into(),
+                        path.into(),
+                        wildmatch::Mode::empty(),
+                    )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pathspec_matches() {
+        let spec = Pathspec::new(&[
+            "src/".to_string(),
+            "*.md".to_string(),
+            "Cargo.toml".to_string(),
+        ]);
+        assert!(spec.matches("src/main.rs"));
+        assert!(spec.matches("src/git/diff.rs"));
+        assert!(spec.matches("docs/guide/intro.md"));
+        assert!(spec.matches("Cargo.toml"));
+        assert!(!spec.matches("srcs/main.rs"));
+        assert!(!spec.matches("sub/Cargo.toml"));
+
+        assert!(Pathspec::new(&[]).matches("anything"));
+    }
+}
@@ -1,9 +1,16 @@
 use std::path::Path;
 
+use gix::ObjectId;
+
 use crate::error::AppError;
+use crate::git::actor::decode_signature;
+use crate::git::message::decode_message;
 use crate::git::repository::open_bare_repo;
-use crate::types::{RefInfo, RefType};
+use crate::git::verify::split_tag_signature;
+use crate::types::{RefInfo, RefType, TagDetails, Tagger};
 
+/// Lists HEAD and every ref under `refs/`. Symbolic refs are followed for
+/// their targets but keep their own name and type.
 pub fn list_refs(repo_path: &Path) -> Result<Vec<RefInfo>, AppError> {
     let repo = open_bare_repo(repo_path)?;
     let mut refs = Vec::new();
@@ -16,10 +23,30 @@ pub fn list_refs(repo_path: &Path) -> Result<Vec<RefInfo>, AppError> {
         .all()
         .map_err(|e| AppError::Git(e.to_string()))?;
 
-    for reference in all.flatten() {
+    let head = repo
+        .find_reference("HEAD")
+        .map_err(|e| AppError::Git(e.to_string()))?;
+    let head_branch = match head.target() {
+        gix::refs::TargetRef::Symbolic(name) => Some(name.as_bstr().to_string()),
+        gix::refs::TargetRef::Object(_) => None,
+    };
+
+    for mut reference in std::iter::once(head).chain(all.flatten()) {
         let full_name = reference.name().as_bstr().to_string();
-        let (name, ref_type) = categorize_ref(&full_name);
+        let (name, mut ref_type) = categorize_ref(&full_name);
+        let symbolic_target = match reference.target() {
+            gix::refs::TargetRef::Symbolic(name) => Some(name.as_bstr().to_string()),
+            gix::refs::TargetRef::Object(_) => None,
+        };
+        if symbolic_target.is_some() && ref_type != RefType::Head {
+            ref_type = RefType::Symbolic;
+        }
 
+        let direct_id = reference.follow_to_object().ok().map(|id| id.detach());
+        let tag = match direct_id {
+            Some(id) => read_tag(&repo, id)?,
+            None => None,
+        };
         let target_oid = reference
             .into_fully_peeled_id()
             .map(|id| id.to_string())
@@ -27,15 +54,60 @@ pub fn list_refs(repo_path: &Path) -> Result<Vec<RefInfo>, AppError> {
 
         refs.push(RefInfo {
             name,
+            is_head: ref_type == RefType::LocalBranch
+                && head_branch.as_deref() == Some(full_name.as_str()),
             full_name,
             target_oid,
+            direct_oid: direct_id.map(|id| id.to_string()).unwrap_or_default(),
             ref_type,
+            symbolic_target,
+            tag,
         });
     }
 
     Ok(refs)
 }
 
+/// Decodes `oid` if it is an annotated tag. The signature block is split off
+/// the message the same way verification does, so SSH signatures are handled
+/// as well as PGP ones.
+fn read_tag(repo: &gix::Repository, oid: ObjectId) -> Result<Option<TagDetails>, AppError> {
+    let object = repo
+        .find_object(oid)
+        .map_err(|e| AppError::Git(e.to_string()))?;
+    if object.kind != gix::object::Kind::Tag {
+        return Ok(None);
+    }
+
+    let (payload, signature) = match split_tag_signature(&object.data) {
+        Some(signed) => (signed.payload, Some(signed.signature)),
+        None => (object.data.clone(), None),
+    };
+    let tag_ref =
+        gix::objs::TagRef::from_bytes(&payload).map_err(|e| AppError::Git(e.to_string()))?;
+    let tagger = tag_ref
+        .tagger()
+        .map_err(|e| AppError::Git(e.to_string()))?
+        .map(|signature| {
+            let signature = decode_signature(signature);
+            Tagger {
+                name: signature.name,
+                email: signature.email,
+                timestamp: signature.timestamp,
+                utc_offset: signature.utc_offset,
+            }
+        });
+
+    Ok(Some(TagDetails {
+        tag_name: tag_ref.name.to_string(),
+        target_oid: tag_ref.target().to_string(),
+        target_kind: tag_ref.target_kind.to_string(),
+        tagger,
+        message: decode_message(tag_ref.message, None),
+        signature: signature.map(|s| String::from_utf8_lossy(&s).into_owned()),
+    }))
+}
+
 fn categorize_ref(full_name: &str) -> (String, RefType) {
     if let Some(name) = full_name.strip_prefix("refs/heads/") {
         (name.to_string(), RefType::LocalBranch)
@@ -43,14 +115,118 @@ fn categorize_ref(full_name: &str) -> (String, RefType) {
         (name.to_string(), RefType::RemoteBranch)
     } else if let Some(name) = full_name.strip_prefix("refs/tags/") {
         (name.to_string(), RefType::Tag)
+    } else if let Some(name) = full_name.strip_prefix("refs/notes/") {
+        (name.to_string(), RefType::Notes)
+    } else if full_name == "refs/stash" {
+        ("stash".to_string(), RefType::Stash)
+    } else if full_name == "HEAD" {
+        (full_name.to_string(), RefType::Head)
     } else {
-        (full_name.to_string(), RefType::LocalBranch)
+        (full_name.to_string(), RefType::Other)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_repo_with_tags() -> (TempDir, TempDir, String) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .env("GIT_COMMITTER_DATE", "1700000000 +0200")
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+        std::fs::write(work.join("file.txt"), "content").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "first"]);
+        git(&["tag", "light"]);
+        git(&["tag", "-a", "v1.0", "-m", "Release 1.0\n\nNotes"]);
+        git(&["tag", "-a", "nested", "-m", "Nested", "v1.0"]);
+        let head = git(&["rev-parse", "HEAD"]);
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+
+        // A tag carrying an SSH signature, which gix leaves in the message.
+        let tag = format!(
+            "object {head}\ntype commit\ntag signed\ntagger Test <test@test.com> 1700000000 +0000\n\n\
+             Signed\n-----BEGIN SSH SIGNATURE-----\nU1NIU0lH\n-----END SSH SIGNATURE-----\n"
+        );
+        let mut mktag = Command::new("git")
+            .arg("-C")
+            .arg(bare_dir.path())
+            .arg("mktag")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        std::io::Write::write_all(&mut mktag.stdin.take().unwrap(), tag.as_bytes()).unwrap();
+        let tag_oid = String::from_utf8(mktag.wait_with_output().unwrap().stdout).unwrap();
+        Command::new("git")
+            .arg("-C")
+            .arg(bare_dir.path())
+            .args(["update-ref", "refs/tags/signed", tag_oid.trim()])
+            .output()
+            .unwrap();
+        (work_dir, bare_dir, head)
+    }
+
+    #[test]
+    fn test_list_refs_tag_details() {
+        let (_work, bare, head) = create_repo_with_tags();
+        let refs = list_refs(bare.path()).unwrap();
+        let find = |name: &str| refs.iter().find(|r| r.full_name == name).unwrap();
+
+        let light = find("refs/tags/light");
+        assert_eq!(light.target_oid, head);
+        assert_eq!(light.direct_oid, head);
+        assert!(light.tag.is_none());
+
+        let release = find("refs/tags/v1.0");
+        assert_eq!(release.target_oid, head);
+        assert_ne!(release.direct_oid, head);
+        let details = release.tag.as_ref().unwrap();
+        assert_eq!(details.tag_name, "v1.0");
+        assert_eq!(details.target_oid, head);
+        assert_eq!(details.target_kind, "commit");
+        assert_eq!(details.message, "Release 1.0\n\nNotes\n");
+        assert!(details.signature.is_none());
+        let tagger = details.tagger.as_ref().unwrap();
+        assert_eq!(tagger.name, "Test");
+        assert_eq!((tagger.timestamp, tagger.utc_offset), (1_700_000_000, 7200));
+
+        let nested = find("refs/tags/nested");
+        assert_eq!(nested.target_oid, head);
+        let details = nested.tag.as_ref().unwrap();
+        assert_eq!(details.target_oid, release.direct_oid);
+        assert_eq!(details.target_kind, "tag");
+
+        let signed = find("refs/tags/signed").tag.as_ref().unwrap();
+        assert_eq!(signed.message, "Signed\n");
+        assert!(signed
+            .signature
+            .as_deref()
+            .unwrap()
+            .starts_with("-----BEGIN SSH SIGNATURE-----"));
+    }
 
     #[test]
     fn test_categorize_ref_local_branch() {
@@ -72,4 +248,66 @@ mod tests {
         assert_eq!(name, "v1.0");
         assert_eq!(ref_type, RefType::Tag);
     }
+
+    #[test]
+    fn test_list_refs_classification() {
+        let (_work, bare, head) = create_repo_with_tags();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(bare.path())
+                .args(args)
+                .output()
+                .unwrap();
+        };
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["notes", "add", "-m", "note"]);
+        git(&["update-ref", "refs/stash", &head]);
+        git(&["update-ref", "refs/pull/1/head", &head]);
+        git(&["branch", "feature"]);
+        let origin_head = "refs/remotes/origin/HEAD";
+        git(&["symbolic-ref", origin_head, "refs/heads/feature"]);
+
+        let refs = list_refs(bare.path()).unwrap();
+        let find = |name: &str| refs.iter().find(|r| r.full_name == name).unwrap();
+        assert_eq!(refs.iter().filter(|r| r.full_name == "HEAD").count(), 1);
+
+        let head_ref = find("HEAD");
+        assert_eq!(head_ref.ref_type, RefType::Head);
+        let branch = head_ref.symbolic_target.clone().unwrap();
+        assert_eq!(head_ref.target_oid, head);
+        assert!(find(&branch).is_head);
+        assert!(!find("refs/heads/feature").is_head);
+
+        let origin_head = find(origin_head);
+        assert_eq!(origin_head.ref_type, RefType::Symbolic);
+        assert_eq!(
+            origin_head.symbolic_target.as_deref(),
+            Some("refs/heads/feature")
+        );
+        assert_eq!(origin_head.target_oid, head);
+
+        assert_eq!(find("refs/notes/commits").ref_type, RefType::Notes);
+        assert_eq!(find("refs/stash").ref_type, RefType::Stash);
+        assert_eq!(find("refs/pull/1/head").ref_type, RefType::Other);
+        assert!(find("refs/tags/v1.0").symbolic_target.is_none());
+    }
+
+    #[test]
+    fn test_categorize_ref_other() {
+        assert_eq!(
+            categorize_ref("refs/notes/commits"),
+            ("commits".to_string(), RefType::Notes)
+        );
+        assert_eq!(
+            categorize_ref("refs/stash"),
+            ("stash".to_string(), RefType::Stash)
+        );
+        assert_eq!(categorize_ref("HEAD"), ("HEAD".to_string(), RefType::Head));
+        assert_eq!(
+            categorize_ref("refs/pull/1/head"),
+            ("refs/pull/1/head".to_string(), RefType::Other)
+        );
+    }
 }
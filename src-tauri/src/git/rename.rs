@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use gix::ObjectId;
+use similar::{capture_diff_slices, get_diff_ratio, Algorithm};
+
+use crate::error::AppError;
+use crate::git::diff::{read_blob_text, TreeChange};
+use crate::types::{DiffOptions, FileStatus};
+
+/// Same meaning as git's `diff.renameLimit`: inexact detection is skipped when
+/// the number of source/destination pairs exceeds this squared.
+const RENAME_LIMIT: usize = 1000;
+
+struct BlobCache<'repo> {
+    repo: &'repo gix::Repository,
+    contents: HashMap<ObjectId, String>,
+}
+
+impl<'repo> BlobCache<'repo> {
+    fn new(repo: &'repo gix::Repository) -> Self {
+        Self {
+            repo,
+            contents: HashMap::new(),
+        }
+    }
+
+    fn load(&mut self, oid: ObjectId) -> Result<&str, AppError> {
+        if !self.contents.contains_key(&oid) {
+            let text = read_blob_text(self.repo, oid)?;
+            self.contents.insert(oid, text);
+        }
+        Ok(self.contents[&oid].as_str())
+    }
+
+    fn text(&self, oid: ObjectId) -> &str {
+        self.contents
+            .get(&oid)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+}
+
+pub fn detect_renames(
+    repo: &gix::Repository,
+    changes: Vec<TreeChange>,
+    options: &DiffOptions,
+) -> Result<Vec<TreeChange>, AppError> {
+    let threshold = options.rename_threshold.min(100);
+    let mut cache = BlobCache::new(repo);
+
+    let (added, rest): (Vec<TreeChange>, Vec<TreeChange>) = changes
+        .into_iter()
+        .partition(|c| c.status == FileStatus::Added);
+    let (deleted, mut result): (Vec<TreeChange>, Vec<TreeChange>) = rest
+        .into_iter()
+        .partition(|c| c.status == FileStatus::Deleted);
+
+    let mut added: Vec<Option<TreeChange>> = added.into_iter().map(Some).collect();
+    let mut deleted: Vec<Option<TreeChange>> = deleted.into_iter().map(Some).collect();
+    let mut pairs: Vec<(usize, usize, u8)> = Vec::new();
+
+    let mut deleted_by_id: HashMap<ObjectId, Vec<usize>> = HashMap::new();
+    for (i, change) in deleted.iter().enumerate() {
+        if let Some(id) = change.as_ref().and_then(|c| c.old_id) {
+            deleted_by_id.entry(id).or_default().push(i);
+        }
+    }
+    for (a, change) in added.iter().enumerate() {
+        let Some(id) = change.as_ref().and_then(|c| c.new_id) else {
+            continue;
+        };
+        if let Some(d) = deleted_by_id.get_mut(&id).and_then(|ids| ids.pop()) {
+            pairs.push((a, d, 100));
+        }
+    }
+
+    let mut used_added = vec![false; added.len()];
+    let mut used_deleted = vec![false; deleted.len()];
+    for &(a, d, _) in &pairs {
+        used_added[a] = true;
+        used_deleted[d] = true;
+    }
+    let remaining_added: Vec<usize> = (0..added.len()).filter(|&a| !used_added[a]).collect();
+    let remaining_deleted: Vec<usize> = (0..deleted.len()).filter(|&d| !used_deleted[d]).collect();
+
+    if remaining_added.len() * remaining_deleted.len() <= RENAME_LIMIT * RENAME_LIMIT {
+        let mut candidates = Vec::new();
+        for &a in &remaining_added {
+            for &d in &remaining_deleted {
+                let (Some(new_id), Some(old_id)) = (
+                    added[a].as_ref().and_then(|c| c.new_id),
+                    deleted[d].as_ref().and_then(|c| c.old_id),
+                ) else {
+                    continue;
+                };
+                let score = content_similarity(&mut cache, old_id, new_id, threshold)?;
+                if score >= threshold {
+                    candidates.push((a, d, score));
+                }
+            }
+        }
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.2));
+
+        for (a, d, score) in candidates {
+            if used_added[a] || used_deleted[d] {
+                continue;
+            }
+            used_added[a] = true;
+            used_deleted[d] = true;
+            pairs.push((a, d, score));
+        }
+    }
+
+    let mut copy_sources: Vec<(String, ObjectId)> = Vec::new();
+    if options.detect_copies {
+        for change in result.iter().chain(deleted.iter().flatten()) {
+            if let (Some(path), Some(id)) = (&change.old_path, change.old_id) {
+                copy_sources.push((path.clone(), id));
+            }
+        }
+    }
+
+    for (a, d, score) in pairs {
+        let (Some(new), Some(old)) = (added[a].take(), deleted[d].take()) else {
+            continue;
+        };
+        result.push(TreeChange {
+            status: FileStatus::Renamed,
+            old_path: old.old_path,
+            new_path: new.new_path,
+            old_id: old.old_id,
+            new_id: new.new_id,
+            similarity: Some(score),
+        });
+    }
+
+    // The rename limit applies to copies as well; past it only sources with
+    // identical content are found.
+    let inexact_copies =
+        added.iter().flatten().count() * copy_sources.len() <= RENAME_LIMIT * RENAME_LIMIT;
+    let mut exact_sources: HashMap<ObjectId, &str> = HashMap::new();
+    for (path, id) in &copy_sources {
+        exact_sources.entry(*id).or_insert(path);
+    }
+
+    for change in added.into_iter().flatten() {
+        let copy = match change.new_id {
+            Some(new_id) if inexact_copies && !copy_sources.is_empty() => {
+                find_copy_source(&mut cache, &copy_sources, new_id, threshold)?
+            }
+            Some(new_id) => exact_sources
+                .get(&new_id)
+                .map(|path| (path.to_string(), new_id, 100)),
+            None => None,
+        };
+        match copy {
+            Some((path, old_id, score)) => result.push(TreeChange {
+                status: FileStatus::Copied,
+                old_path: Some(path),
+                new_path: change.new_path,
+                old_id: Some(old_id),
+                new_id: change.new_id,
+                similarity: Some(score),
+            }),
+            None => result.push(change),
+        }
+    }
+    result.extend(deleted.into_iter().flatten());
+
+    result.sort_by(|x, y| x.path().cmp(y.path()));
+    Ok(result)
+}
+
+fn find_copy_source(
+    cache: &mut BlobCache,
+    sources: &[(String, ObjectId)],
+    new_id: ObjectId,
+    threshold: u8,
+) -> Result<Option<(String, ObjectId, u8)>, AppError> {
+    let mut best: Option<(String, ObjectId, u8)> = None;
+    for (path, old_id) in sources {
+        let score = content_similarity(cache, *old_id, new_id, threshold)?;
+        if score >= threshold && best.as_ref().is_none_or(|b| score > b.2) {
+            best = Some((path.clone(), *old_id, score));
+        }
+    }
+    Ok(best)
+}
+
+fn content_similarity(
+    cache: &mut BlobCache,
+    old_id: ObjectId,
+    new_id: ObjectId,
+    threshold: u8,
+) -> Result<u8, AppError> {
+    if old_id == new_id {
+        return Ok(100);
+    }
+
+    let old_len = cache.load(old_id)?.len();
+    let new_len = cache.load(new_id)?.len();
+
+    // Like git, don't bother diffing pairs whose sizes alone rule out a match.
+    let (min, max) = (old_len.min(new_len), old_len.max(new_len));
+    if min == 0 || min * 100 < max * threshold as usize {
+        return Ok(0);
+    }
+
+    Ok(line_similarity(cache.text(old_id), cache.text(new_id)))
+}
+
+fn line_similarity(old: &str, new: &str) -> u8 {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = capture_diff_slices(Algorithm::Myers, &old_lines, &new_lines);
+    let ratio = get_diff_ratio(&ops, old_lines.len(), new_lines.len());
+    (ratio * 100.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_similarity() {
+        assert_eq!(line_similarity("a\nb\nc\nd\n", "a\nb\nc\nd\n"), 100);
+        assert_eq!(line_similarity("a\nb\nc\nd\n", "a\nb\nc\nx\n"), 75);
+        assert_eq!(line_similarity("a\nb\n", "x\ny\n"), 0);
+    }
+}
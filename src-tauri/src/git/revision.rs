@@ -0,0 +1,125 @@
+use gix::revision::plumbing::Spec;
+use gix::ObjectId;
+
+use crate::error::AppError;
+
+/// Resolves a revspec such as `main`, `v1.2^`, `HEAD~3` or a short hash to the
+/// commit it names, peeling tags along the way. `A...B` resolves to the merge
+/// base of `A` and `B`, which is what `git diff A...B` uses as its old side.
+pub fn resolve_commit(repo: &gix::Repository, spec: &str) -> Result<ObjectId, AppError> {
+    let peeled = match spec.split_once("...") {
+        Some((theirs, ours)) => format!("{}...{}", peel_base(theirs), peel_base(ours)),
+        None => peel_base(spec),
+    };
+    let parsed = repo
+        .rev_parse(peeled.as_str())
+        .map_err(|e| AppError::Git(format!("Invalid revision '{spec}': {e}")))?
+        .detach();
+
+    let id = match parsed {
+        Spec::Include(id) => id,
+        Spec::Merge { theirs, ours } => repo
+            .merge_base(theirs, ours)
+            .map_err(|e| AppError::Git(e.to_string()))?
+            .detach(),
+        _ => {
+            return Err(AppError::Git(format!(
+                "Revision '{spec}' does not name a single commit"
+            )))
+        }
+    };
+
+    let commit = repo
+        .find_object(id)
+        .map_err(|e| AppError::Git(e.to_string()))?
+        .peel_to_commit()
+        .map_err(|e| AppError::Git(e.to_string()))?;
+    Ok(commit.id)
+}
+
+/// Unlike git, gix refuses to navigate to the parent of an annotated tag, so
+/// `v1.2^` or `v1.2~3` become `v1.2^{commit}^` and `v1.2^{commit}~3`.
+fn peel_base(spec: &str) -> String {
+    match spec.find(['^', '~']) {
+        Some(index) if index > 0 && !spec[index..].starts_with("^{") => {
+            format!("{}^{{commit}}{}", &spec[..index], &spec[index..])
+        }
+        _ => spec.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::repository::open_bare_repo;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_repo_with_history() -> (TempDir, TempDir) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+        };
+        git(&["init", "-b", "main"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+
+        for i in 1..=4 {
+            std::fs::write(work.join("file.txt"), format!("{i}\n")).unwrap();
+            git(&["add", "."]);
+            git(&["commit", "-m", &format!("commit {i}")]);
+        }
+        git(&["tag", "-a", "v1.2", "-m", "release", "HEAD~1"]);
+        git(&["checkout", "-b", "feature", "HEAD~2"]);
+        std::fs::write(work.join("feature.txt"), "feature\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "feature"]);
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        (work_dir, bare_dir)
+    }
+
+    fn rev_parse(dir: &std::path::Path, spec: &str) -> String {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["rev-parse", spec])
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn test_resolve_commit() {
+        let (_work, bare) = create_repo_with_history();
+        let repo = open_bare_repo(bare.path()).unwrap();
+        let resolve = |spec: &str| resolve_commit(&repo, spec).unwrap().to_string();
+
+        for spec in ["main", "main~3", "HEAD~2", "v1.2^", "feature"] {
+            assert_eq!(
+                resolve(spec),
+                rev_parse(bare.path(), &format!("{spec}^{{commit}}"))
+            );
+        }
+
+        let head = rev_parse(bare.path(), "main");
+        assert_eq!(resolve(&head[..8]), head);
+        assert_eq!(resolve("main...feature"), rev_parse(bare.path(), "main~2"));
+
+        assert!(resolve_commit(&repo, "does-not-exist").is_err());
+        assert!(resolve_commit(&repo, "main~2..main").is_err());
+    }
+}
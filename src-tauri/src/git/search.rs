@@ -0,0 +1,200 @@
+use std::path::Path;
+
+use gix::ObjectId;
+
+use crate::error::AppError;
+use crate::git::log::TextMatcher;
+use crate::git::refs::list_refs;
+use crate::git::repository::{discover_bare_repos, open_bare_repo};
+use crate::git::walk::CommitWalk;
+use crate::types::{
+    CommitInfo, CommitSearchHit, CommitSearchOptions, CommitSearchSummary, LogSort, RefType,
+};
+
+/// Searches every bare repository under `root` for commits whose message or
+/// author matches `query`, or whose OID starts with it. Hits are handed to
+/// `on_hit` as soon as they are found; a repository that can't be read is
+/// listed in the summary instead of failing the whole search.
+pub fn search_commits(
+    root: &Path,
+    query: &str,
+    options: &CommitSearchOptions,
+    mut on_hit: impl FnMut(&CommitSearchHit),
+) -> Result<CommitSearchSummary, AppError> {
+    let matcher = TextMatcher::new(query, options.use_regex, true)?;
+    let oid_prefix = query.trim().to_lowercase();
+    let oid_prefix = (oid_prefix.len() >= 4 && oid_prefix.chars().all(|c| c.is_ascii_hexdigit()))
+        .then_some(oid_prefix);
+
+    let mut summary = CommitSearchSummary {
+        repos_searched: 0,
+        hits: 0,
+        failed_repos: Vec::new(),
+    };
+    for repo_path in discover_bare_repos(root, options.max_depth)? {
+        let repo_name = repo_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let is_match = |commit: &CommitInfo| {
+            let author = format!("{} <{}>", commit.author_name, commit.author_email);
+            matcher.is_match(&commit.message)
+                || matcher.is_match(&author)
+                || oid_prefix
+                    .as_deref()
+                    .is_some_and(|prefix| commit.oid.starts_with(prefix))
+        };
+
+        let result = search_repo(
+            &repo_path,
+            options.max_results_per_repo,
+            is_match,
+            |commit| {
+                summary.hits += 1;
+                on_hit(&CommitSearchHit {
+                    repo_path: repo_path.to_string_lossy().to_string(),
+                    repo_name: repo_name.clone(),
+                    commit,
+                });
+            },
+        );
+        match result {
+            Ok(()) => summary.repos_searched += 1,
+            Err(_) => summary
+                .failed_repos
+                .push(repo_path.to_string_lossy().to_string()),
+        }
+    }
+    Ok(summary)
+}
+
+/// Walks the history reachable from every ref, newest first.
+fn search_repo(
+    repo_path: &Path,
+    limit: usize,
+    is_match: impl Fn(&CommitInfo) -> bool,
+    mut on_match: impl FnMut(CommitInfo),
+) -> Result<(), AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let mut tips: Vec<ObjectId> = Vec::new();
+    for reference in list_refs(repo_path)? {
+        if reference.ref_type == RefType::Notes {
+            continue;
+        }
+        let Ok(oid) = ObjectId::from_hex(reference.target_oid.as_bytes()) else {
+            continue;
+        };
+        // Tags can point at trees or blobs, which have no history to search.
+        let is_commit = repo
+            .find_object(oid)
+            .is_ok_and(|object| object.kind == gix::object::Kind::Commit);
+        if is_commit && !tips.contains(&oid) {
+            tips.push(oid);
+        }
+    }
+
+    let mut walk = CommitWalk::new(&repo, &tips, LogSort::default())?;
+    let mut found = 0;
+    while found < limit {
+        let Some(commit) = walk.next_commit()? else {
+            break;
+        };
+        if is_match(&commit) {
+            found += 1;
+            on_match(commit);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_bare_repo(root: &Path, name: &str, messages: &[&str]) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+        };
+        git(&["init"]);
+        git(&["config", "user.name", name]);
+        git(&["config", "user.email", "dev@example.com"]);
+        for (i, message) in messages.iter().enumerate() {
+            std::fs::write(work.join("file.txt"), i.to_string()).unwrap();
+            git(&["add", "."]);
+            git(&["commit", "-m", message]);
+        }
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(root.join(format!("{name}.git")))
+            .output()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_search_commits_across_repos() {
+        let root = TempDir::new().unwrap();
+        create_bare_repo(root.path(), "alpha", &["init", "fix: overflow in parser"]);
+        create_bare_repo(root.path(), "beta", &["init", "Fix overflow again", "docs"]);
+        let broken = root.path().join("broken.git");
+        std::fs::create_dir_all(broken.join("objects")).unwrap();
+        std::fs::create_dir_all(broken.join("refs")).unwrap();
+        std::fs::write(broken.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::write(broken.join("config"), "[core\n").unwrap();
+
+        let mut hits = Vec::new();
+        let summary = search_commits(
+            root.path(),
+            "overflow",
+            &CommitSearchOptions::default(),
+            |hit| hits.push(hit.clone()),
+        )
+        .unwrap();
+
+        let mut found: Vec<(&str, &str)> = hits
+            .iter()
+            .map(|h| (h.repo_name.as_str(), h.commit.subject.as_str()))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("alpha.git", "fix: overflow in parser"),
+                ("beta.git", "Fix overflow again"),
+            ]
+        );
+        assert_eq!(summary.hits, 2);
+        assert_eq!(summary.repos_searched, 2);
+        assert_eq!(summary.failed_repos.len(), 1);
+
+        let by_author = search_commits(
+            root.path(),
+            "^beta",
+            &CommitSearchOptions {
+                use_regex: true,
+                ..CommitSearchOptions::default()
+            },
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(by_author.hits, 3);
+
+        let oid = hits[0].commit.oid[..8].to_string();
+        let mut by_oid = Vec::new();
+        search_commits(root.path(), &oid, &CommitSearchOptions::default(), |hit| {
+            by_oid.push(hit.commit.oid.clone())
+        })
+        .unwrap();
+        assert_eq!(by_oid, vec![hits[0].commit.oid.clone()]);
+    }
+}
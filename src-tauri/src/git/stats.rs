@@ -0,0 +1,178 @@
+use std::path::Path;
+
+use similar::DiffOp;
+
+use crate::error::AppError;
+use crate::git::attributes::TreeAttributes;
+use crate::git::diff::{
+    changes_between, commit_trees, diff_lines, is_binary_change, is_blank_change, read_blob,
+};
+use crate::git::repository::open_bare_repo;
+use crate::git::revision::resolve_commit;
+use crate::types::{CommitStats, DiffOptions, FileStat};
+
+pub fn get_commit_stats(
+    repo_path: &Path,
+    oid_str: &str,
+    options: &DiffOptions,
+) -> Result<CommitStats, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    stats_for_commit(&repo, oid_str, options)
+}
+
+/// Computes stats for several commits while opening the repository only once,
+/// which keeps per-row badges in the commit list cheap.
+pub fn get_commit_stats_batch(
+    repo_path: &Path,
+    oids: &[String],
+    options: &DiffOptions,
+) -> Result<Vec<CommitStats>, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    oids.iter()
+        .map(|oid| stats_for_commit(&repo, oid, options))
+        .collect()
+}
+
+fn stats_for_commit(
+    repo: &gix::Repository,
+    oid_str: &str,
+    options: &DiffOptions,
+) -> Result<CommitStats, AppError> {
+    let oid = resolve_commit(repo, oid_str)?;
+
+    let (old_tree_id, new_tree_id) = commit_trees(repo, oid)?;
+    let changes = changes_between(repo, old_tree_id, new_tree_id, options)?;
+    let mut attributes = TreeAttributes::new(repo, new_tree_id)?;
+
+    let mut files = Vec::with_capacity(changes.len());
+    for change in changes {
+        let path = change.path().to_string();
+        let old_data = change.old_id.map(|id| read_blob(repo, id)).transpose()?;
+        let new_data = change.new_id.map(|id| read_blob(repo, id)).transpose()?;
+        let old = old_data.as_deref().unwrap_or_default();
+        let new = new_data.as_deref().unwrap_or_default();
+
+        let is_binary = is_binary_change(&mut attributes, &path, old, new)?;
+        let (insertions, deletions) = if is_binary {
+            (0, 0)
+        } else {
+            count_line_changes(
+                &String::from_utf8_lossy(old),
+                &String::from_utf8_lossy(new),
+                options,
+            )
+        };
+
+        files.push(FileStat {
+            path,
+            old_path: change.old_path,
+            status: change.status,
+            insertions,
+            deletions,
+            is_binary,
+        });
+    }
+
+    Ok(CommitStats {
+        oid: oid.to_string(),
+        files_changed: files.len(),
+        insertions: files.iter().map(|f| f.insertions).sum(),
+        deletions: files.iter().map(|f| f.deletions).sum(),
+        files,
+    })
+}
+
+fn count_line_changes(
+    old_content: &str,
+    new_content: &str,
+    options: &DiffOptions,
+) -> (usize, usize) {
+    let old_lines: Vec<&str> = old_content.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new_content.split_inclusive('\n').collect();
+
+    diff_lines(&old_lines, &new_lines, options)
+        .iter()
+        .fold((0, 0), |(insertions, deletions), op| match op {
+            DiffOp::Equal { .. } => (insertions, deletions),
+            _ if options.ignore_blank_lines && is_blank_change(op, &old_lines, &new_lines) => {
+                (insertions, deletions)
+            }
+            _ => (
+                insertions + op.new_range().len(),
+                deletions + op.old_range().len(),
+            ),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FileStatus;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_repo_with_changes() -> (TempDir, TempDir, String) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+
+        std::fs::write(work.join("a.txt"), "1\n2\n3\n").unwrap();
+        std::fs::write(work.join("gone.txt"), "x\ny\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "first"]);
+
+        std::fs::write(work.join("a.txt"), "1\ntwo\n3\n4\n").unwrap();
+        std::fs::remove_file(work.join("gone.txt")).unwrap();
+        std::fs::write(work.join("blob.bin"), b"\0\x01\x02").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "second"]);
+        let head = git(&["rev-parse", "HEAD"]);
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+
+        (work_dir, bare_dir, head)
+    }
+
+    #[test]
+    fn test_get_commit_stats() {
+        let (_work, bare, head) = create_repo_with_changes();
+        let stats = get_commit_stats(bare.path(), &head, &DiffOptions::default()).unwrap();
+
+        assert_eq!(stats.files_changed, 3);
+        assert_eq!(stats.insertions, 2);
+        assert_eq!(stats.deletions, 3);
+
+        let modified = stats.files.iter().find(|f| f.path == "a.txt").unwrap();
+        assert_eq!((modified.insertions, modified.deletions), (2, 1));
+        let binary = stats.files.iter().find(|f| f.path == "blob.bin").unwrap();
+        assert!(binary.is_binary);
+        assert_eq!(binary.status, FileStatus::Added);
+    }
+
+    #[test]
+    fn test_get_commit_stats_batch() {
+        let (_work, bare, head) = create_repo_with_changes();
+        let oids = vec![head.clone(), head];
+        let stats = get_commit_stats_batch(bare.path(), &oids, &DiffOptions::default()).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].insertions, stats[1].insertions);
+    }
+}
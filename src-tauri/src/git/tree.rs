@@ -0,0 +1,289 @@
+use std::path::Path;
+
+use gix::object::tree::EntryKind;
+use gix::ObjectId;
+
+use crate::error::AppError;
+use crate::git::attributes::TreeAttributes;
+use crate::git::diff::{
+    blob_at, commit_tree_id, decode_tree, is_binary_change, lookup_path, read_blob,
+};
+use crate::git::log::touches_path;
+use crate::git::repository::open_bare_repo;
+use crate::git::revision::resolve_commit;
+use crate::git::walk::CommitWalk;
+use crate::types::{
+    BlobContent, BlobOptions, CommitInfo, LogSort, TreeEntry, TreeEntryKind, TreeListing,
+    TreeOptions,
+};
+
+/// Lists the directory at `<rev>:<path>` (HEAD and the root by default),
+/// directories first.
+pub fn list_tree(
+    repo_path: &Path,
+    rev: Option<&str>,
+    path: &str,
+    options: &TreeOptions,
+) -> Result<TreeListing, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let oid = resolve_commit(&repo, rev.unwrap_or("HEAD"))?;
+    let path = path.trim_matches('/').to_string();
+    let root_id = commit_tree_id(&repo, oid)?;
+
+    let tree_id = if path.is_empty() {
+        root_id
+    } else {
+        let (mode, id) = lookup_path(&repo, root_id, &path)?
+            .ok_or_else(|| AppError::Git(format!("'{path}' does not exist in {oid}")))?;
+        if !mode.is_tree() {
+            return Err(AppError::Git(format!("'{path}' is not a directory")));
+        }
+        id
+    };
+
+    let mut entries = Vec::new();
+    for (name, mode, entry_oid) in decode_tree(&repo, tree_id)? {
+        let kind = match mode.kind() {
+            EntryKind::Tree => TreeEntryKind::Tree,
+            EntryKind::Commit => TreeEntryKind::Submodule,
+            EntryKind::Link => TreeEntryKind::Symlink,
+            EntryKind::Blob | EntryKind::BlobExecutable => TreeEntryKind::Blob,
+        };
+        let size = match kind {
+            TreeEntryKind::Blob | TreeEntryKind::Symlink => Some(
+                repo.find_header(entry_oid)
+                    .map_err(|e| AppError::Git(e.to_string()))?
+                    .size(),
+            ),
+            _ => None,
+        };
+        let entry_path = if path.is_empty() {
+            name.clone()
+        } else {
+            format!("{path}/{name}")
+        };
+        entries.push(TreeEntry {
+            name,
+            path: entry_path,
+            mode: format!("{:06o}", mode.value()),
+            kind,
+            oid: entry_oid.to_string(),
+            size,
+            last_commit: None,
+        });
+    }
+    entries.sort_by(|a, b| {
+        (a.kind != TreeEntryKind::Tree)
+            .cmp(&(b.kind != TreeEntryKind::Tree))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    if options.include_last_commit {
+        fill_last_commits(&repo, oid, &path, &mut entries)?;
+    }
+
+    Ok(TreeListing {
+        commit_oid: oid.to_string(),
+        path,
+        entries,
+    })
+}
+
+/// Finds the newest commit touching each entry in a single walk. Commits that
+/// leave the directory itself as one of their parents had it are skipped
+/// without looking at the entries.
+fn fill_last_commits(
+    repo: &gix::Repository,
+    start: ObjectId,
+    dir: &str,
+    entries: &mut [TreeEntry],
+) -> Result<(), AppError> {
+    let mut remaining: Vec<usize> = (0..entries.len()).collect();
+    let mut walk = CommitWalk::new(repo, &[start], LogSort::default())?;
+    while !remaining.is_empty() {
+        let Some(commit) = walk.next_commit()? else {
+            break;
+        };
+        if !touches_dir(repo, &commit, dir)? {
+            continue;
+        }
+
+        let mut untouched = Vec::with_capacity(remaining.len());
+        for index in remaining {
+            if touches_path(repo, &commit, &entries[index].path)? {
+                entries[index].last_commit = Some(commit.clone());
+            } else {
+                untouched.push(index);
+            }
+        }
+        remaining = untouched;
+    }
+    Ok(())
+}
+
+fn touches_dir(repo: &gix::Repository, commit: &CommitInfo, dir: &str) -> Result<bool, AppError> {
+    if !dir.is_empty() {
+        return touches_path(repo, commit, dir);
+    }
+    let oid = ObjectId::from_hex(commit.oid.as_bytes())
+        .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?;
+    let tree = commit_tree_id(repo, oid)?;
+    for parent in &commit.parent_oids {
+        let parent = ObjectId::from_hex(parent.as_bytes())
+            .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?;
+        if commit_tree_id(repo, parent)? == tree {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Reads the file at `<rev>:<path>`. Text is cut at `max_bytes` on a character
+/// boundary; binary content, by attributes or sniffing, is not returned.
+pub fn read_file(
+    repo_path: &Path,
+    rev: Option<&str>,
+    path: &str,
+    options: &BlobOptions,
+) -> Result<BlobContent, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let oid = resolve_commit(&repo, rev.unwrap_or("HEAD"))?;
+    let path = path.trim_matches('/').to_string();
+    let blob = blob_at(&repo, oid, &path)?
+        .ok_or_else(|| AppError::Git(format!("'{path}' is not a file in {oid}")))?;
+
+    let data = read_blob(&repo, blob)?;
+    let mut attributes = TreeAttributes::new(&repo, commit_tree_id(&repo, oid)?)?;
+    let is_binary = is_binary_change(&mut attributes, &path, &data, &data)?;
+    let truncated = data.len() > options.max_bytes;
+
+    let content = (!is_binary).then(|| {
+        let mut end = data.len().min(options.max_bytes);
+        while end < data.len() && end > 0 && data[end] & 0xC0 == 0x80 {
+            end -= 1;
+        }
+        String::from_utf8_lossy(&data[..end]).into_owned()
+    });
+
+    Ok(BlobContent {
+        commit_oid: oid.to_string(),
+        path,
+        oid: blob.to_string(),
+        size: data.len() as u64,
+        is_binary,
+        content,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn create_repo_for_tree() -> (TempDir, TempDir, Vec<String>) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(work)
+                .args(args)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+
+        let mut commits = Vec::new();
+        std::fs::create_dir(work.join("src")).unwrap();
+        std::fs::write(work.join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        std::fs::write(work.join("README.md"), "héllo\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "first"]);
+        commits.push(git(&["rev-parse", "HEAD"]));
+
+        std::fs::write(work.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(work.join("logo.png"), b"\x89PNG\0\0").unwrap();
+        std::os::unix::fs::symlink("README.md", work.join("link")).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "second"]);
+        commits.push(git(&["rev-parse", "HEAD"]));
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        (work_dir, bare_dir, commits)
+    }
+
+    #[test]
+    fn test_list_tree() {
+        let (_work, bare, commits) = create_repo_for_tree();
+        let options = TreeOptions {
+            include_last_commit: true,
+        };
+        let root = list_tree(bare.path(), None, "", &options).unwrap();
+        let names: Vec<(&str, TreeEntryKind, &str)> = root
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.kind, e.mode.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("src", TreeEntryKind::Tree, "040000"),
+                ("README.md", TreeEntryKind::Blob, "100644"),
+                ("link", TreeEntryKind::Symlink, "120000"),
+                ("logo.png", TreeEntryKind::Blob, "100644"),
+            ]
+        );
+        assert_eq!(root.entries[1].size, Some(7));
+        assert!(root.entries[0].size.is_none());
+        let last: Vec<&str> = root
+            .entries
+            .iter()
+            .map(|e| e.last_commit.as_ref().unwrap().oid.as_str())
+            .collect();
+        assert_eq!(
+            last,
+            vec![&commits[1], &commits[0], &commits[1], &commits[1]]
+        );
+
+        let src = list_tree(bare.path(), Some(&commits[0]), "src/", &options).unwrap();
+        assert_eq!(src.entries.len(), 1);
+        assert_eq!(src.entries[0].path, "src/lib.rs");
+        assert_eq!(src.entries[0].last_commit.as_ref().unwrap().oid, commits[0]);
+
+        assert!(list_tree(bare.path(), None, "README.md", &options).is_err());
+        assert!(list_tree(bare.path(), None, "missing", &options).is_err());
+    }
+
+    #[test]
+    fn test_read_file() {
+        let (_work, bare, _commits) = create_repo_for_tree();
+        let readme = read_file(bare.path(), None, "README.md", &BlobOptions::default()).unwrap();
+        assert_eq!(readme.content.as_deref(), Some("héllo\n"));
+        assert_eq!(readme.size, 7);
+        assert!(!readme.truncated);
+
+        // Cutting inside "é" backs off to the character boundary.
+        let options = BlobOptions { max_bytes: 2 };
+        let cut = read_file(bare.path(), None, "README.md", &options).unwrap();
+        assert_eq!(cut.content.as_deref(), Some("h"));
+        assert!(cut.truncated);
+
+        let logo = read_file(bare.path(), None, "logo.png", &BlobOptions::default()).unwrap();
+        assert!(logo.is_binary);
+        assert!(logo.content.is_none());
+
+        assert!(read_file(bare.path(), None, "src", &BlobOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,462 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use gix::ObjectId;
+use tempfile::NamedTempFile;
+
+use crate::error::AppError;
+use crate::git::repository::open_bare_repo;
+use crate::git::revision::resolve_commit;
+use crate::types::{SignatureFormat, SignatureStatus, SignatureVerification, VerifyOptions};
+
+/// The namespace git signs commits and tags under with `ssh-keygen -Y sign`.
+const SSH_NAMESPACE: &str = "git";
+
+const SIGNATURE_MARKERS: [(&str, SignatureFormat); 4] = [
+    ("-----BEGIN PGP SIGNATURE-----", SignatureFormat::Gpg),
+    ("-----BEGIN PGP MESSAGE-----", SignatureFormat::Gpg),
+    ("-----BEGIN SSH SIGNATURE-----", SignatureFormat::Ssh),
+    ("-----BEGIN SIGNED MESSAGE-----", SignatureFormat::X509),
+];
+
+/// A detached signature and the exact bytes it was made over.
+pub struct Signed {
+    pub signature: Vec<u8>,
+    pub payload: Vec<u8>,
+}
+
+struct Outcome {
+    status: SignatureStatus,
+    signer: Option<String>,
+    key_id: Option<String>,
+}
+
+pub fn verify_commit(
+    repo_path: &Path,
+    rev: &str,
+    options: &VerifyOptions,
+) -> Result<SignatureVerification, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let oid = resolve_commit(&repo, rev)?;
+    let commit = repo
+        .find_object(oid)
+        .map_err(|e| AppError::Git(e.to_string()))?
+        .into_commit();
+    let signed = commit
+        .signature()
+        .map_err(|e| AppError::Git(e.to_string()))?
+        .map(|(signature, data)| Signed {
+            signature: signature.to_vec(),
+            payload: data.to_bstring().into(),
+        });
+    verify(&repo, oid, signed, options)
+}
+
+/// `tag` must name an annotated tag; lightweight tags have nothing to verify.
+pub fn verify_tag(
+    repo_path: &Path,
+    tag: &str,
+    options: &VerifyOptions,
+) -> Result<SignatureVerification, AppError> {
+    let repo = open_bare_repo(repo_path)?;
+    let object = repo
+        .rev_parse_single(tag)
+        .map_err(|e| AppError::Git(e.to_string()))?
+        .object()
+        .map_err(|e| AppError::Git(e.to_string()))?;
+    if object.kind != gix::object::Kind::Tag {
+        return Err(AppError::Git(format!("'{tag}' is not an annotated tag")));
+    }
+    verify(&repo, object.id, split_tag_signature(&object.data), options)
+}
+
+fn verify(
+    repo: &gix::Repository,
+    oid: ObjectId,
+    signed: Option<Signed>,
+    options: &VerifyOptions,
+) -> Result<SignatureVerification, AppError> {
+    let Some(signed) = signed else {
+        return Ok(SignatureVerification {
+            oid: oid.to_string(),
+            format: None,
+            status: SignatureStatus::Unsigned,
+            signer: None,
+            key_id: None,
+        });
+    };
+
+    let format = signature_format(&signed.signature);
+    let outcome = match format {
+        Some(SignatureFormat::Gpg) => verify_gpg(&signed, options.gpg_home.as_deref())?,
+        Some(SignatureFormat::Ssh) => {
+            verify_ssh(&signed, allowed_signers(repo, options).as_deref())?
+        }
+        _ => {
+            return Err(AppError::Git(format!(
+                "Unsupported signature format on {oid}"
+            )))
+        }
+    };
+    Ok(SignatureVerification {
+        oid: oid.to_string(),
+        format,
+        status: outcome.status,
+        signer: outcome.signer,
+        key_id: outcome.key_id,
+    })
+}
+
+fn signature_format(signature: &[u8]) -> Option<SignatureFormat> {
+    SIGNATURE_MARKERS
+        .iter()
+        .find(|(marker, _)| signature.starts_with(marker.as_bytes()))
+        .map(|(_, format)| *format)
+}
+
+/// Tag signatures are appended to the message, so, like git, everything from
+/// the last line that opens a signature block onwards is the signature.
+pub fn split_tag_signature(data: &[u8]) -> Option<Signed> {
+    let start = SIGNATURE_MARKERS
+        .iter()
+        .filter_map(|(marker, _)| {
+            let marker = marker.as_bytes();
+            (0..data.len())
+                .filter(|&i| i == 0 || data[i - 1] == b'\n')
+                .rfind(|&i| data[i..].starts_with(marker))
+        })
+        .max()?;
+    Some(Signed {
+        signature: data[start..].to_vec(),
+        payload: data[..start].to_vec(),
+    })
+}
+
+fn allowed_signers(repo: &gix::Repository, options: &VerifyOptions) -> Option<PathBuf> {
+    match &options.allowed_signers {
+        Some(path) => Some(PathBuf::from(path)),
+        None => repo
+            .config_snapshot()
+            .string("gpg.ssh.allowedSignersFile")
+            .map(|path| PathBuf::from(path.to_string())),
+    }
+}
+
+fn verify_gpg(signed: &Signed, gpg_home: Option<&str>) -> Result<Outcome, AppError> {
+    let signature = signature_file(&signed.signature)?;
+    let mut command = Command::new("gpg");
+    if let Some(home) = gpg_home {
+        command.arg("--homedir").arg(home);
+    }
+    command
+        .args(["--batch", "--no-tty", "--status-fd=1", "--verify"])
+        .arg(signature.path())
+        .arg("-");
+    let output = run_with_input(command, &signed.payload)?;
+
+    parse_gpg_status(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+        AppError::Git(format!(
+            "gpg could not check the signature: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    })
+}
+
+/// Reads gpg's `--status-fd` lines. Returns `None` if gpg never got as far as
+/// looking at a signature.
+fn parse_gpg_status(status: &str) -> Option<Outcome> {
+    let mut outcome: Option<Outcome> = None;
+    let mut fingerprint = None;
+    for line in status.lines() {
+        let Some(line) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        let (keyword, args) = line.split_once(' ').unwrap_or((line, ""));
+        let status = match keyword {
+            "GOODSIG" => SignatureStatus::Good,
+            "BADSIG" => SignatureStatus::Bad,
+            "EXPSIG" | "EXPKEYSIG" => SignatureStatus::ExpiredKey,
+            "REVKEYSIG" => SignatureStatus::RevokedKey,
+            // The sixth field is the error code; 9 means the key is missing.
+            "ERRSIG" if args.split(' ').nth(5) == Some("9") => SignatureStatus::UnknownKey,
+            "ERRSIG" => SignatureStatus::Bad,
+            "VALIDSIG" => {
+                fingerprint = args.split(' ').next().map(str::to_string);
+                continue;
+            }
+            _ => continue,
+        };
+        let (key_id, signer) = args.split_once(' ').unwrap_or((args, ""));
+        // ERRSIG carries algorithms and a timestamp instead of a user id.
+        let signer = if keyword == "ERRSIG" { "" } else { signer };
+        outcome = Some(Outcome {
+            status,
+            signer: Some(signer.to_string()).filter(|s| !s.is_empty()),
+            key_id: Some(key_id.to_string()).filter(|k| !k.is_empty()),
+        });
+    }
+
+    let mut outcome = outcome?;
+    if fingerprint.is_some() {
+        outcome.key_id = fingerprint;
+    }
+    Some(outcome)
+}
+
+/// Like git: the signer is whichever principal in `allowed_signers` owns the
+/// key. A valid signature by a key nobody is allowed to sign with is reported
+/// as an unknown key rather than bad.
+fn verify_ssh(signed: &Signed, allowed_signers: Option<&Path>) -> Result<Outcome, AppError> {
+    let signature = signature_file(&signed.signature)?;
+    let principal = match allowed_signers {
+        Some(file) => find_principal(file, signature.path())?,
+        None => None,
+    };
+
+    let mut command = Command::new("ssh-keygen");
+    match (allowed_signers, &principal) {
+        (Some(file), Some(principal)) => {
+            command
+                .args(["-Y", "verify", "-f"])
+                .arg(file)
+                .args(["-I", principal]);
+        }
+        _ => {
+            command.args(["-Y", "check-novalidate"]);
+        }
+    }
+    command
+        .args(["-n", SSH_NAMESPACE, "-s"])
+        .arg(signature.path());
+    let output = run_with_input(command, &signed.payload)?;
+
+    let status = match (output.status.success(), principal.is_some()) {
+        (false, _) => SignatureStatus::Bad,
+        (true, true) => SignatureStatus::Good,
+        (true, false) => SignatureStatus::UnknownKey,
+    };
+    // "Good "git" signature for <principal> with ED25519 key SHA256:..."
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let key_id = stdout
+        .split_once(" key ")
+        .map(|(_, key)| key.trim().to_string());
+    Ok(Outcome {
+        status,
+        signer: principal,
+        key_id,
+    })
+}
+
+fn find_principal(allowed_signers: &Path, signature: &Path) -> Result<Option<String>, AppError> {
+    let output = Command::new("ssh-keygen")
+        .args(["-Y", "find-principals", "-f"])
+        .arg(allowed_signers)
+        .arg("-s")
+        .arg(signature)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty()))
+}
+
+fn run_with_input(mut command: Command, input: &[u8]) -> Result<Output, AppError> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    Ok(child.wait_with_output()?)
+}
+
+/// Both gpg and ssh-keygen want a detached signature as a file. It is created
+/// exclusively, readable only by us, and removed again when dropped.
+fn signature_file(signature: &[u8]) -> Result<NamedTempFile, AppError> {
+    let mut file = tempfile::Builder::new()
+        .prefix("sukis-git-")
+        .suffix(".sig")
+        .tempfile()?;
+    file.write_all(signature)?;
+    file.flush()?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_gpg_status() {
+        let good = parse_gpg_status(
+            "[GNUPG:] NEWSIG\n\
+             [GNUPG:] GOODSIG 1234ABCD Test User <test@example.com>\n\
+             [GNUPG:] VALIDSIG FINGERPRINT1234ABCD 2024-01-01 1704067200 0 4 0 22 10 00 FINGERPRINT1234ABCD\n\
+             [GNUPG:] TRUST_UNDEFINED 0 pgp\n",
+        )
+        .unwrap();
+        assert_eq!(good.status, SignatureStatus::Good);
+        assert_eq!(good.signer.as_deref(), Some("Test User <test@example.com>"));
+        assert_eq!(good.key_id.as_deref(), Some("FINGERPRINT1234ABCD"));
+
+        let missing = parse_gpg_status(
+            "[GNUPG:] ERRSIG 1234ABCD 22 10 00 1704067200 9 -\n[GNUPG:] NO_PUBKEY 1234ABCD\n",
+        )
+        .unwrap();
+        assert_eq!(missing.status, SignatureStatus::UnknownKey);
+        assert_eq!(missing.key_id.as_deref(), Some("1234ABCD"));
+        assert!(missing.signer.is_none());
+
+        let bad = parse_gpg_status("[GNUPG:] BADSIG 1234ABCD Test User\n").unwrap();
+        assert_eq!(bad.status, SignatureStatus::Bad);
+        assert!(parse_gpg_status("[GNUPG:] FAILURE gpg-exit 33554433\n").is_none());
+    }
+
+    #[test]
+    fn test_split_tag_signature() {
+        let data = b"object 1234\ntype commit\ntag v1\n\nrelease\n-----BEGIN SSH SIGNATURE-----\nabc\n-----END SSH SIGNATURE-----\n";
+        let signed = split_tag_signature(data).unwrap();
+        assert!(signed.payload.ends_with(b"release\n"));
+        assert_eq!(
+            signature_format(&signed.signature),
+            Some(SignatureFormat::Ssh)
+        );
+        assert!(split_tag_signature(b"object 1234\n\nnot signed\n").is_none());
+    }
+
+    /// Returns the work dir, a bare clone, the allowed signers file, and the
+    /// unsigned and signed commits.
+    fn create_ssh_signed_repo() -> (TempDir, TempDir, PathBuf, String, String) {
+        let work_dir = TempDir::new().unwrap();
+        let work = work_dir.path().join("work");
+        std::fs::create_dir(&work).unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(&work)
+                .args(args)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+
+        let key = work_dir.path().join("key");
+        Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+            .arg(&key)
+            .output()
+            .unwrap();
+        let public_key = std::fs::read_to_string(key.with_extension("pub")).unwrap();
+        let allowed = work_dir.path().join("allowed_signers");
+        std::fs::write(&allowed, format!("test@test.com {public_key}")).unwrap();
+
+        git(&["init"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "gpg.format", "ssh"]);
+        git(&["config", "user.signingkey", &key.to_string_lossy()]);
+
+        std::fs::write(work.join("a.txt"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "unsigned"]);
+        let unsigned = git(&["rev-parse", "HEAD"]);
+
+        std::fs::write(work.join("a.txt"), "two\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-S", "-m", "signed"]);
+        let signed = git(&["rev-parse", "HEAD"]);
+        git(&["tag", "-s", "v1", "-m", "release"]);
+
+        let bare_dir = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("clone")
+            .arg("--bare")
+            .arg(&work)
+            .arg(bare_dir.path())
+            .output()
+            .unwrap();
+        (work_dir, bare_dir, allowed, unsigned, signed)
+    }
+
+    #[test]
+    fn test_verify_ssh_signed_commit_and_tag() {
+        let (_work, bare, allowed, unsigned, signed) = create_ssh_signed_repo();
+        let options = VerifyOptions {
+            gpg_home: None,
+            allowed_signers: Some(allowed.to_string_lossy().to_string()),
+        };
+
+        let good = verify_commit(bare.path(), &signed, &options).unwrap();
+        assert_eq!(good.format, Some(SignatureFormat::Ssh));
+        assert_eq!(good.status, SignatureStatus::Good);
+        assert_eq!(good.signer.as_deref(), Some("test@test.com"));
+        assert!(good.key_id.unwrap().starts_with("SHA256:"));
+
+        let unknown = verify_commit(bare.path(), &signed, &VerifyOptions::default()).unwrap();
+        assert_eq!(unknown.status, SignatureStatus::UnknownKey);
+
+        let none = verify_commit(bare.path(), &unsigned, &options).unwrap();
+        assert_eq!(none.status, SignatureStatus::Unsigned);
+
+        let repo = open_bare_repo(bare.path()).unwrap();
+        let commit_info = |oid: &str| {
+            let oid = ObjectId::from_hex(oid.as_bytes()).unwrap();
+            crate::git::log::parse_commit_from_repo(&repo, oid).unwrap()
+        };
+        assert!(commit_info(&signed).is_signed);
+        assert!(!commit_info(&unsigned).is_signed);
+
+        let tag = verify_tag(bare.path(), "v1", &options).unwrap();
+        assert_eq!(tag.status, SignatureStatus::Good);
+        assert!(verify_tag(bare.path(), "HEAD", &options).is_err());
+    }
+
+    #[test]
+    fn test_verify_ssh_tampered_commit() {
+        let (_work, bare, allowed, _unsigned, signed) = create_ssh_signed_repo();
+        let raw = Command::new("git")
+            .arg("-C")
+            .arg(bare.path())
+            .args(["cat-file", "commit", &signed])
+            .output()
+            .unwrap()
+            .stdout;
+        let forged = String::from_utf8(raw)
+            .unwrap()
+            .replace("\nsigned\n", "\nforged\n");
+
+        let mut child = Command::new("git")
+            .arg("-C")
+            .arg(bare.path())
+            .args(["hash-object", "-t", "commit", "-w", "--stdin"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(forged.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        let forged_oid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        let options = VerifyOptions {
+            gpg_home: None,
+            allowed_signers: Some(allowed.to_string_lossy().to_string()),
+        };
+        let result = verify_commit(bare.path(), &forged_oid, &options).unwrap();
+        assert_eq!(result.status, SignatureStatus::Bad);
+    }
+}
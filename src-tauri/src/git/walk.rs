@@ -0,0 +1,555 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use gix::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::git::log::parse_commit_from_repo;
+use crate::types::{CommitInfo, LogOrder, LogSort};
+
+#[cfg(test)]
+thread_local! {
+    /// Commits taken off the queue of a date-ordered walk on this thread, so
+    /// tests can check how far a walk went.
+    pub static COMMITS_WALKED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// What a cursor carries: the sort and tips the walk was started with, and how
+/// many commits it has returned. Its size does not grow with the history.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct CursorState {
+    sort: LogSort,
+    tips: Vec<String>,
+    offset: usize,
+}
+
+enum Strategy {
+    /// Date orders walk lazily with a priority queue.
+    Incremental,
+    /// Topological and reverse orders need the whole history up front.
+    Listed {
+        order: Arc<Vec<ObjectId>>,
+        position: usize,
+    },
+}
+
+/// Full orders are keyed by repository, tips and sort. Commits are immutable,
+/// so an entry never goes stale; only the most recent few are kept.
+type OrderKey = (PathBuf, Vec<ObjectId>, LogSort);
+
+const ORDER_CACHE_SIZE: usize = 4;
+
+static ORDER_CACHE: Mutex<Vec<(OrderKey, Arc<Vec<ObjectId>>)>> = Mutex::new(Vec::new());
+
+/// The queue and visited set of a date-ordered walk, parked between pages so
+/// that resuming does not replay the commits already returned.
+struct WalkState {
+    queue: BinaryHeap<(i64, ObjectId)>,
+    seen: HashSet<ObjectId>,
+    pending: HashMap<ObjectId, CommitInfo>,
+}
+
+/// Parked walks are keyed like full orders plus the number of commits
+/// returned, which is all a cursor records.
+type WalkKey = (PathBuf, Vec<ObjectId>, LogSort, usize);
+
+const WALK_CACHE_SIZE: usize = 4;
+
+static WALK_CACHE: Mutex<Vec<(WalkKey, WalkState)>> = Mutex::new(Vec::new());
+
+/// A commit walk whose state can be saved in an opaque cursor and resumed
+/// later, so pages of a log neither repeat nor skip commits even when the
+/// history has many merges.
+pub struct CommitWalk<'repo> {
+    repo: &'repo gix::Repository,
+    sort: LogSort,
+    tips: Vec<ObjectId>,
+    strategy: Strategy,
+    queue: BinaryHeap<(i64, ObjectId)>,
+    seen: HashSet<ObjectId>,
+    pending: HashMap<ObjectId, CommitInfo>,
+    /// Commits returned by a date-ordered walk so far.
+    returned: usize,
+    /// The commit `next_commit` returned last, with its queue key.
+    last: Option<(i64, ObjectId)>,
+}
+
+impl<'repo> CommitWalk<'repo> {
+    /// Topological and reverse orders walk the whole history for the first
+    /// page; the order is cached so that resuming from a cursor does not.
+    pub fn new(
+        repo: &'repo gix::Repository,
+        tips: &[ObjectId],
+        sort: LogSort,
+    ) -> Result<Self, AppError> {
+        if !sort.reverse && sort.order != LogOrder::Topological {
+            return Self::incremental(repo, tips, sort);
+        }
+
+        let key = (repo.git_dir().to_path_buf(), tips.to_vec(), sort);
+        let cached = {
+            let cache = ORDER_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+            cache
+                .iter()
+                .find(|(cached, _)| *cached == key)
+                .map(|(_, order)| order.clone())
+        };
+        let order = match cached {
+            Some(order) => order,
+            None => {
+                let order = Arc::new(full_order(&mut Self::incremental(repo, tips, sort)?)?);
+                let mut cache = ORDER_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+                if cache.len() >= ORDER_CACHE_SIZE {
+                    cache.remove(0);
+                }
+                cache.push((key, order.clone()));
+                order
+            }
+        };
+
+        let mut walk = Self::incremental(repo, &[], sort)?;
+        walk.tips = tips.to_vec();
+        walk.strategy = Strategy::Listed { order, position: 0 };
+        Ok(walk)
+    }
+
+    fn incremental(
+        repo: &'repo gix::Repository,
+        tips: &[ObjectId],
+        sort: LogSort,
+    ) -> Result<Self, AppError> {
+        let mut walk = Self {
+            repo,
+            sort,
+            tips: tips.to_vec(),
+            strategy: Strategy::Incremental,
+            queue: BinaryHeap::new(),
+            seen: HashSet::new(),
+            pending: HashMap::new(),
+            returned: 0,
+            last: None,
+        };
+        for tip in tips {
+            walk.enqueue(*tip)?;
+        }
+        Ok(walk)
+    }
+
+    /// The cursor decides the sort, so a resumed walk always continues the way
+    /// it started. A date-ordered walk that is no longer parked is replayed
+    /// from its tips.
+    pub fn resume(repo: &'repo gix::Repository, cursor: &str) -> Result<Self, AppError> {
+        let state = decode_cursor(cursor)?;
+        let tips = parse_oids(&state.tips)?;
+        let mut walk = Self::new(repo, &tips, state.sort)?;
+        if let Strategy::Listed { position, .. } = &mut walk.strategy {
+            *position = state.offset;
+            return Ok(walk);
+        }
+
+        let key = (repo.git_dir().to_path_buf(), tips, state.sort, state.offset);
+        let parked = {
+            let mut cache = WALK_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+            cache
+                .iter()
+                .position(|(parked, _)| *parked == key)
+                .map(|index| cache.remove(index).1)
+        };
+        match parked {
+            Some(parked) => {
+                walk.queue = parked.queue;
+                walk.seen = parked.seen;
+                walk.pending = parked.pending;
+                walk.returned = state.offset;
+            }
+            None => {
+                while walk.returned < state.offset {
+                    if walk.next_commit()?.is_none() {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(walk)
+    }
+
+    fn key(&self, commit: &CommitInfo) -> i64 {
+        match self.sort.order {
+            LogOrder::AuthorDate => commit.timestamp,
+            _ => commit.committer_timestamp,
+        }
+    }
+
+    fn parents(&self, commit: &CommitInfo) -> Result<Vec<ObjectId>, AppError> {
+        let count = if self.sort.first_parent {
+            1
+        } else {
+            usize::MAX
+        };
+        parse_oids(&commit.parent_oids[..commit.parent_oids.len().min(count)])
+    }
+
+    fn enqueue(&mut self, oid: ObjectId) -> Result<(), AppError> {
+        if !self.seen.insert(oid) {
+            return Ok(());
+        }
+        let commit = parse_commit_from_repo(self.repo, oid)?;
+        self.queue.push((self.key(&commit), oid));
+        self.pending.insert(oid, commit);
+        Ok(())
+    }
+
+    /// The committer date of the next commit of a walk in committer date
+    /// order, which no later commit exceeds unless clocks were skewed.
+    pub fn next_committer_date(&self) -> Option<i64> {
+        match self.strategy {
+            Strategy::Incremental if self.sort.order == LogOrder::CommitterDate => {
+                self.queue.peek().map(|(key, _)| *key)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn next_commit(&mut self) -> Result<Option<CommitInfo>, AppError> {
+        if let Strategy::Listed { order, position } = &mut self.strategy {
+            let Some(oid) = order.get(*position).copied() else {
+                return Ok(None);
+            };
+            *position += 1;
+            return parse_commit_from_repo(self.repo, oid).map(Some);
+        }
+
+        let Some((key, oid)) = self.queue.pop() else {
+            return Ok(None);
+        };
+        #[cfg(test)]
+        COMMITS_WALKED.with(|count| count.set(count.get() + 1));
+        let commit = match self.pending.remove(&oid) {
+            Some(commit) => commit,
+            None => parse_commit_from_repo(self.repo, oid)?,
+        };
+        for parent in self.parents(&commit)? {
+            self.enqueue(parent)?;
+        }
+        self.returned += 1;
+        self.last = Some((key, oid));
+        Ok(Some(commit))
+    }
+
+    /// Puts the commit `next_commit` just returned back at the front of the
+    /// walk so that it becomes the first commit of the next page.
+    pub fn push_back(&mut self, commit: CommitInfo) -> Result<(), AppError> {
+        if let Strategy::Listed { position, .. } = &mut self.strategy {
+            *position = position.saturating_sub(1);
+            return Ok(());
+        }
+
+        if let Some((key, oid)) = self.last.take() {
+            self.queue.push((key, oid));
+            self.pending.insert(oid, commit);
+            self.returned -= 1;
+        }
+        Ok(())
+    }
+
+    /// Returns `None` once the walk is exhausted. A date-ordered walk is
+    /// parked so that resuming from the cursor picks up where it stopped.
+    pub fn into_cursor(self) -> Option<String> {
+        let offset = match &self.strategy {
+            Strategy::Listed { order, position } => {
+                if *position >= order.len() {
+                    return None;
+                }
+                *position
+            }
+            Strategy::Incremental => {
+                self.queue.peek()?;
+                let key = (
+                    self.repo.git_dir().to_path_buf(),
+                    self.tips.clone(),
+                    self.sort,
+                    self.returned,
+                );
+                let mut cache = WALK_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+                cache.retain(|(parked, _)| *parked != key);
+                if cache.len() >= WALK_CACHE_SIZE {
+                    cache.remove(0);
+                }
+                cache.push((
+                    key,
+                    WalkState {
+                        queue: self.queue,
+                        seen: self.seen,
+                        pending: self.pending,
+                    },
+                ));
+                self.returned
+            }
+        };
+        Some(encode_cursor(&CursorState {
+            sort: self.sort,
+            tips: self.tips.iter().map(ObjectId::to_string).collect(),
+            offset,
+        }))
+    }
+}
+
+/// Orders everything reachable from `tips` for the listed strategies.
+fn full_order(walk: &mut CommitWalk) -> Result<Vec<ObjectId>, AppError> {
+    let mut order = Vec::new();
+    let mut commits = Vec::new();
+    while let Some(commit) = walk.next_commit()? {
+        let oid = ObjectId::from_hex(commit.oid.as_bytes())
+            .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))?;
+        order.push(oid);
+        commits.push((oid, walk.parents(&commit)?));
+    }
+
+    if walk.sort.order == LogOrder::Topological {
+        order = topological_order(&commits);
+    }
+    if walk.sort.reverse {
+        order.reverse();
+    }
+    Ok(order)
+}
+
+/// Like `git log --topo-order`: no parent before all of its children, and
+/// each line of history is shown in one go rather than interleaved by date,
+/// with a merge's side branch before its mainline. `commits` must be in date
+/// order, which decides between commits that are ready at the same time.
+fn topological_order(commits: &[(ObjectId, Vec<ObjectId>)]) -> Vec<ObjectId> {
+    let mut children: HashMap<ObjectId, usize> = HashMap::new();
+    for (_, parents) in commits {
+        for parent in parents {
+            *children.entry(*parent).or_default() += 1;
+        }
+    }
+    let parents: HashMap<ObjectId, &Vec<ObjectId>> = commits
+        .iter()
+        .map(|(oid, parents)| (*oid, parents))
+        .collect();
+
+    let mut ready: Vec<ObjectId> = commits
+        .iter()
+        .map(|(oid, _)| *oid)
+        .filter(|oid| !children.contains_key(oid))
+        .collect();
+    ready.reverse();
+
+    let mut order = Vec::with_capacity(commits.len());
+    while let Some(oid) = ready.pop() {
+        order.push(oid);
+        for parent in parents[&oid].iter() {
+            if let Some(count) = children.get_mut(parent) {
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(*parent);
+                }
+            }
+        }
+    }
+    order
+}
+
+fn parse_oids(oids: &[String]) -> Result<Vec<ObjectId>, AppError> {
+    oids.iter()
+        .map(|oid| {
+            ObjectId::from_hex(oid.as_bytes())
+                .map_err(|e| AppError::Git(format!("Invalid OID: {e}")))
+        })
+        .collect()
+}
+
+fn encode_cursor(state: &CursorState) -> String {
+    let json = serde_json::to_vec(state).unwrap_or_default();
+    URL_SAFE_NO_PAD.encode(json)
+}
+
+fn decode_cursor(cursor: &str) -> Result<CursorState, AppError> {
+    let invalid = || AppError::Git("Invalid log cursor".to_string());
+    let raw = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    serde_json::from_slice(&raw).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    /// Builds commits on the empty tree from `(name, time, parents)`, in order.
+    fn create_repo_with_dates(
+        commits: &[(&str, i64, &[&str])],
+    ) -> (TempDir, HashMap<String, ObjectId>) {
+        let dir = TempDir::new().unwrap();
+        let git = |args: &[&str], time: i64| {
+            let date = format!("@{time} +0000");
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(args)
+                .env("GIT_AUTHOR_NAME", "Test")
+                .env("GIT_AUTHOR_EMAIL", "test@test.com")
+                .env("GIT_COMMITTER_NAME", "Test")
+                .env("GIT_COMMITTER_EMAIL", "test@test.com")
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init", "--bare"], 0);
+        let tree = git(&["hash-object", "-t", "tree", "-w", "/dev/null"], 0);
+
+        let mut oids: HashMap<String, ObjectId> = HashMap::new();
+        for (name, time, parents) in commits {
+            let mut args = vec!["commit-tree".to_string(), tree.clone(), "-m".to_string()];
+            args.push(name.to_string());
+            for parent in *parents {
+                args.push("-p".to_string());
+                args.push(oids[*parent].to_string());
+            }
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let oid = git(&args, *time);
+            oids.insert(
+                name.to_string(),
+                ObjectId::from_hex(oid.as_bytes()).unwrap(),
+            );
+        }
+        (dir, oids)
+    }
+
+    fn subjects(commits: impl IntoIterator<Item = CommitInfo>) -> Vec<String> {
+        commits.into_iter().map(|c| c.subject).collect()
+    }
+
+    #[test]
+    fn test_cursor_survives_deep_clock_skew() {
+        // B is dated after E, its child, and is only reached again from the
+        // frontier several pages after it was returned through M.
+        let (dir, oids) = create_repo_with_dates(&[
+            ("D", 5, &[]),
+            ("B", 90, &["D"]),
+            ("E", 15, &["B"]),
+            ("F", 17, &["E"]),
+            ("C", 20, &["F"]),
+            ("A", 50, &["C"]),
+            ("M", 100, &["A", "B"]),
+        ]);
+        let repo = gix::open(dir.path()).unwrap();
+        let tips = [oids["M"]];
+
+        let mut single = CommitWalk::new(&repo, &tips, LogSort::default()).unwrap();
+        let expected = subjects(std::iter::from_fn(|| single.next_commit().unwrap()));
+        assert_eq!(expected, vec!["M", "B", "A", "C", "F", "E", "D"]);
+
+        let mut paged = Vec::new();
+        let mut walk = CommitWalk::new(&repo, &tips, LogSort::default()).unwrap();
+        loop {
+            paged.extend(walk.next_commit().unwrap());
+            let Some(cursor) = walk.into_cursor() else {
+                break;
+            };
+            walk = CommitWalk::resume(&repo, &cursor).unwrap();
+        }
+        assert_eq!(subjects(paged), expected);
+    }
+
+    #[test]
+    fn test_cursor_stays_small_on_long_history() {
+        let names: Vec<String> = (0..200).map(|i| format!("c{i}")).collect();
+        let mut commits: Vec<(&str, i64, Vec<&str>)> = Vec::new();
+        for (i, name) in names.iter().enumerate() {
+            let parents = if i == 0 {
+                vec![]
+            } else {
+                vec![names[i - 1].as_str()]
+            };
+            commits.push((name, 1000 + i as i64, parents));
+        }
+        let commits: Vec<(&str, i64, &[&str])> = commits
+            .iter()
+            .map(|(name, time, parents)| (*name, *time, parents.as_slice()))
+            .collect();
+        let (dir, oids) = create_repo_with_dates(&commits);
+        let repo = gix::open(dir.path()).unwrap();
+        let tips = [oids["c199"]];
+
+        let mut paged = Vec::new();
+        let mut cursors = Vec::new();
+        let mut walk = CommitWalk::new(&repo, &tips, LogSort::default()).unwrap();
+        loop {
+            for _ in 0..3 {
+                paged.extend(walk.next_commit().unwrap());
+            }
+            let Some(cursor) = walk.into_cursor() else {
+                break;
+            };
+            assert!(cursor.len() < 200, "cursor grew to {} bytes", cursor.len());
+            cursors.push(cursor.clone());
+            walk = CommitWalk::resume(&repo, &cursor).unwrap();
+        }
+        let expected: Vec<String> = names.iter().rev().cloned().collect();
+        assert_eq!(subjects(paged), expected);
+
+        // A cursor whose walk is no longer parked replays it instead.
+        let mut replayed = CommitWalk::resume(&repo, &cursors[10]).unwrap();
+        assert_eq!(replayed.next_commit().unwrap().unwrap().subject, "c166");
+    }
+
+    #[test]
+    fn test_listed_order_is_reused_across_pages() {
+        let (dir, oids) =
+            create_repo_with_dates(&[("A", 10, &[]), ("B", 20, &["A"]), ("C", 30, &["B"])]);
+        let repo = gix::open(dir.path()).unwrap();
+        let sort = LogSort {
+            reverse: true,
+            ..LogSort::default()
+        };
+        let mut walk = CommitWalk::new(&repo, &[oids["C"]], sort).unwrap();
+        assert_eq!(walk.next_commit().unwrap().unwrap().subject, "A");
+        let cursor = walk.into_cursor().unwrap();
+
+        let key = (repo.git_dir().to_path_buf(), vec![oids["C"]], sort);
+        assert!(ORDER_CACHE
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(cached, _)| *cached == key));
+        let mut resumed = CommitWalk::resume(&repo, &cursor).unwrap();
+        let rest = subjects(std::iter::from_fn(|| resumed.next_commit().unwrap()));
+        assert_eq!(rest, vec!["B", "C"]);
+    }
+
+    fn oid(byte: char) -> ObjectId {
+        ObjectId::from_hex(byte.to_string().repeat(40).as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let state = CursorState {
+            sort: LogSort {
+                order: LogOrder::AuthorDate,
+                reverse: false,
+                first_parent: true,
+            },
+            tips: vec![oid('1').to_string(), oid('2').to_string()],
+            offset: 3,
+        };
+        assert_eq!(decode_cursor(&encode_cursor(&state)).unwrap(), state);
+        assert!(decode_cursor("not a cursor").is_err());
+    }
+
+    #[test]
+    fn test_topological_order_keeps_branches_together() {
+        // a <- b <- d (merge) and a <- c <- d, with c newer than b by date.
+        let (a, b, c, d) = (oid('a'), oid('b'), oid('c'), oid('d'));
+        let by_date = vec![(d, vec![b, c]), (c, vec![a]), (b, vec![a]), (a, vec![])];
+        assert_eq!(topological_order(&by_date), vec![d, c, b, a]);
+    }
+}
@@ -20,12 +20,32 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             get_repository_info,
             discover_repositories,
+            search_commits,
             get_commit_log,
             get_commit_detail,
+            get_file_history,
+            get_blame,
+            get_tree,
+            get_file_content,
+            export_archive,
+            grep_tree,
+            pickaxe_search,
+            verify_commit_signature,
+            verify_tag_signature,
             list_refs,
+            list_branch_status,
             get_commit_dag,
             get_diff_text,
             get_commit_diff_text,
+            get_diff,
+            get_commit_diff,
+            get_changed_files,
+            get_path_diff,
+            get_commit_parent_diffs,
+            get_combined_diff,
+            get_combined_diff_text,
+            get_commit_stats,
+            get_commit_stats_batch,
             list_usb_devices,
             scan_usb_device,
             clone_repository,
@@ -12,6 +12,21 @@ pub fn emit_progress(app_handle: Option<&AppHandle>, event_name: &str, step: &st
     }
 }
 
+pub fn emit_progress_detail(
+    app_handle: Option<&AppHandle>,
+    event_name: &str,
+    step: &str,
+    detail: String,
+) {
+    if let Some(handle) = app_handle {
+        let _ = handle.emit(event_name, &ProgressEvent {
+            step: step.to_string(),
+            is_error: false,
+            detail: Some(detail),
+        });
+    }
+}
+
 pub fn emit_error(app_handle: Option<&AppHandle>, event_name: &str, step: &str) {
     if let Some(handle) = app_handle {
         let _ = handle.emit(event_name, &ProgressEvent {
@@ -5,12 +5,26 @@ pub struct CommitInfo {
     pub oid: String,
     pub short_oid: String,
     pub message: String,
+    pub subject: String,
+    pub body: String,
+    pub trailers: Vec<CommitTrailer>,
     pub author_name: String,
     pub author_email: String,
     pub committer_name: String,
     pub committer_email: String,
     pub parent_oids: Vec<String>,
+    /// Author time; offsets are in seconds east of UTC.
     pub timestamp: i64,
+    pub author_utc_offset: i32,
+    pub committer_timestamp: i64,
+    pub committer_utc_offset: i32,
+    pub is_signed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommitTrailer {
+    pub key: String,
+    pub value: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -18,14 +32,75 @@ pub enum RefType {
     LocalBranch,
     RemoteBranch,
     Tag,
+    Notes,
+    Stash,
+    Head,
+    /// A symbolic ref other than HEAD, e.g. `refs/remotes/origin/HEAD`.
+    Symbolic,
+    /// Anything else under `refs/`, e.g. `refs/pull/1/head`.
+    Other,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RefInfo {
     pub name: String,
     pub full_name: String,
+    /// Fully peeled, e.g. the commit an annotated tag points to.
     pub target_oid: String,
+    /// The object the ref itself names, e.g. the tag object of an annotated tag.
+    pub direct_oid: String,
     pub ref_type: RefType,
+    /// The ref a symbolic ref points to, e.g. `refs/heads/main` for HEAD.
+    pub symbolic_target: Option<String>,
+    /// Whether this is the branch HEAD points to.
+    pub is_head: bool,
+    /// Set for annotated tags only.
+    pub tag: Option<TagDetails>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagDetails {
+    /// The name recorded in the tag object, which may differ from the ref name.
+    pub tag_name: String,
+    /// The object the tag points to; another tag when tags are nested.
+    pub target_oid: String,
+    pub target_kind: String,
+    pub tagger: Option<Tagger>,
+    /// Without the signature block.
+    pub message: String,
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tagger {
+    pub name: String,
+    pub email: String,
+    pub timestamp: i64,
+    pub utc_offset: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BranchStatusOptions {
+    /// Compare every branch against this revision instead of its upstream or
+    /// the default branch.
+    pub base: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchStatus {
+    pub name: String,
+    pub full_name: String,
+    pub target_oid: String,
+    pub is_head: bool,
+    /// The tracking ref configured by `branch.<name>.remote` and `.merge`.
+    pub upstream: Option<String>,
+    /// What `ahead` and `behind` were counted against; `None` when there was
+    /// nothing to compare with.
+    pub base: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    pub last_commit: CommitInfo,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +135,73 @@ pub struct CommitDag {
     pub edges: Vec<DagEdge>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHistoryEntry {
+    pub commit: CommitInfo,
+    pub path: String,
+    pub old_path: Option<String>,
+    pub blob_oid: Option<String>,
+    pub status: FileStatus,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlameOptions {
+    pub ignore_whitespace: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlameHunk {
+    pub start_line: usize,
+    pub line_count: usize,
+    pub orig_start_line: usize,
+    pub orig_path: String,
+    pub commit_oid: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub timestamp: i64,
+    pub summary: String,
+    pub previous_oid: Option<String>,
+    pub previous_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileBlame {
+    pub commit_oid: String,
+    pub path: String,
+    pub lines: Vec<String>,
+    pub hunks: Vec<BlameHunk>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFilter {
+    pub author: Option<String>,
+    pub committer: Option<String>,
+    pub message: Option<String>,
+    pub use_regex: bool,
+    pub case_insensitive: bool,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum LogOrder {
+    #[default]
+    CommitterDate,
+    AuthorDate,
+    Topological,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct LogSort {
+    pub order: LogOrder,
+    pub reverse: bool,
+    pub first_parent: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitLogPage {
     pub commits: Vec<CommitInfo>,
@@ -67,6 +209,404 @@ pub struct CommitLogPage {
     pub cursor: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TreeEntryKind {
+    Blob,
+    Tree,
+    Symlink,
+    Submodule,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeEntry {
+    pub name: String,
+    pub path: String,
+    /// Octal, as printed by `git ls-tree`.
+    pub mode: String,
+    pub kind: TreeEntryKind,
+    pub oid: String,
+    /// Only known for blobs and symlinks.
+    pub size: Option<u64>,
+    pub last_commit: Option<CommitInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeListing {
+    pub commit_oid: String,
+    pub path: String,
+    pub entries: Vec<TreeEntry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TreeOptions {
+    pub include_last_commit: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlobOptions {
+    pub max_bytes: usize,
+}
+
+impl Default for BlobOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobContent {
+    pub commit_oid: String,
+    pub path: String,
+    pub oid: String,
+    pub size: u64,
+    pub is_binary: bool,
+    /// Cut at `max_bytes`; `None` for binary content.
+    pub content: Option<String>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    #[default]
+    Tar,
+    TarGz,
+    Zip,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveOptions {
+    pub format: ArchiveFormat,
+    /// Directory or file to export; the whole tree when empty.
+    pub path: String,
+    /// Prepended to every entry, like `git archive --prefix`.
+    pub prefix: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveResult {
+    pub commit_oid: String,
+    pub output_path: String,
+    pub file_count: usize,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrepOptions {
+    pub use_regex: bool,
+    pub case_insensitive: bool,
+    pub pathspecs: Vec<String>,
+    pub context_lines: usize,
+    pub max_matches: usize,
+}
+
+impl Default for GrepOptions {
+    fn default() -> Self {
+        Self {
+            use_regex: false,
+            case_insensitive: false,
+            pathspecs: Vec::new(),
+            context_lines: 0,
+            max_matches: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrepMatch {
+    pub path: String,
+    pub line_number: usize,
+    pub line: String,
+    /// Byte ranges of the matches within `line`.
+    pub match_ranges: Vec<(usize, usize)>,
+    pub context_before: Vec<String>,
+    pub context_after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrepResult {
+    pub commit_oid: String,
+    pub matches: Vec<GrepMatch>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PickaxeOptions {
+    pub use_regex: bool,
+    pub case_insensitive: bool,
+    pub pathspecs: Vec<String>,
+    /// Commits reachable from this revision are not searched, as in `since..rev`.
+    pub since: Option<String>,
+    pub max_results: usize,
+}
+
+impl Default for PickaxeOptions {
+    fn default() -> Self {
+        Self {
+            use_regex: false,
+            case_insensitive: false,
+            pathspecs: Vec::new(),
+            since: None,
+            max_results: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickaxeFile {
+    pub path: String,
+    pub old_count: usize,
+    pub new_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickaxeHit {
+    pub commit: CommitInfo,
+    pub files: Vec<PickaxeFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitSearchOptions {
+    pub use_regex: bool,
+    pub max_depth: usize,
+    pub max_results_per_repo: usize,
+}
+
+impl Default for CommitSearchOptions {
+    fn default() -> Self {
+        Self {
+            use_regex: false,
+            max_depth: 3,
+            max_results_per_repo: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitSearchHit {
+    pub repo_path: String,
+    pub repo_name: String,
+    pub commit: CommitInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitSearchSummary {
+    pub repos_searched: usize,
+    pub hits: usize,
+    pub failed_repos: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SignatureFormat {
+    Gpg,
+    Ssh,
+    X509,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Unsigned,
+    Good,
+    Bad,
+    UnknownKey,
+    ExpiredKey,
+    RevokedKey,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VerifyOptions {
+    /// GnuPG home directory holding the keyring; gpg's default when unset.
+    pub gpg_home: Option<String>,
+    /// Falls back to the repository's `gpg.ssh.allowedSignersFile`.
+    pub allowed_signers: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureVerification {
+    pub oid: String,
+    pub format: Option<SignatureFormat>,
+    pub status: SignatureStatus,
+    pub signer: Option<String>,
+    pub key_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FileStatus {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+    Copied,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Context,
+    Addition,
+    Deletion,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+    pub content: String,
+    pub missing_newline: bool,
+    pub spans: Vec<InlineSpan>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InlineSpan {
+    pub text: String,
+    pub changed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hunk {
+    pub header: String,
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiff {
+    pub path: String,
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub status: FileStatus,
+    pub similarity: Option<u8>,
+    pub is_binary: bool,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+    pub preview: Option<BinaryPreview>,
+    pub hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryPreview {
+    pub mime_type: String,
+    pub old_base64: Option<String>,
+    pub new_base64: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedFile {
+    pub path: String,
+    pub old_path: Option<String>,
+    pub status: FileStatus,
+    pub similarity: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStat {
+    pub path: String,
+    pub old_path: Option<String>,
+    pub status: FileStatus,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub is_binary: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitStats {
+    pub oid: String,
+    pub files: Vec<FileStat>,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParentDiff {
+    pub parent_oid: String,
+    pub files: Vec<FileDiff>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CombinedDiffLine {
+    pub origins: Vec<DiffLineKind>,
+    pub old_lines: Vec<Option<usize>>,
+    pub new_line: Option<usize>,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CombinedHunk {
+    pub header: String,
+    pub old_starts: Vec<usize>,
+    pub old_lines: Vec<usize>,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<CombinedDiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CombinedFileDiff {
+    pub path: String,
+    pub is_binary: bool,
+    pub hunks: Vec<CombinedHunk>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum DiffAlgorithm {
+    #[default]
+    Myers,
+    Patience,
+    Lcs,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum InlineGranularity {
+    None,
+    #[default]
+    Word,
+    Char,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiffOptions {
+    pub detect_renames: bool,
+    pub detect_copies: bool,
+    pub rename_threshold: u8,
+    pub include_image_previews: bool,
+    pub context_lines: usize,
+    pub ignore_whitespace: bool,
+    pub ignore_eol: bool,
+    pub ignore_blank_lines: bool,
+    pub algorithm: DiffAlgorithm,
+    pub inline_granularity: InlineGranularity,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            detect_renames: true,
+            detect_copies: false,
+            rename_threshold: 50,
+            include_image_previews: false,
+            context_lines: 3,
+            ignore_whitespace: false,
+            ignore_eol: false,
+            ignore_blank_lines: false,
+            algorithm: DiffAlgorithm::Myers,
+            inline_granularity: InlineGranularity::Word,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CloneResult {
     pub source_path: String,
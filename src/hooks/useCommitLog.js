@@ -3,8 +3,8 @@ import * as R from "ramda";
 import { invoke } from "../lib/tauri";
 import { SWR_KEYS } from "../lib/swr";
 
-const fetchCommitLog = (repoPath, startOid, limit) =>
-  invoke("get_commit_log", { repoPath, startOid, limit });
+const fetchCommitLog = (repoPath, cursor, limit) =>
+  invoke("get_commit_log", { repoPath, cursor, limit });
 
 export function useCommitLog(repoPath, cursor, limit = 50) {
   const { data, error, isLoading } = useSWR(